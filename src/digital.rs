@@ -1,11 +1,30 @@
 //! The digital module is about boolean functions and digital systems.
 
-use rand::Rng;
+use std::io;
+
+use rand::{
+    Rng,
+    RngCore,
+};
 
 pub use crate::genetic::Individual;
+use crate::genetic::Crossover;
+use crate::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use crate::genetic::genome::{
+    genome_crossover,
+    genome_distance,
+    Gene,
+    Genome,
+};
+use crate::genetic::multiobjective::Complexity;
+use crate::genetic::niching::GenotypeDistance;
 
 /// Basic logic gates. Operations that can be done between two bits.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum BinOp {
     AND,
     OR,
@@ -40,6 +59,24 @@ impl BinOp {
     }
 }
 
+impl Checkpoint for BinOp {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write(self.to_str());
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<BinOp> {
+        let name : String = reader.read()?;
+        for i in 0..6 {
+            let operation_type = BinOp::from_u32(i);
+            if operation_type.to_str() == name {
+                return Ok(operation_type);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown BinOp in checkpoint: {}", name)))
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, PartialEq, Debug)]
 enum AndPseudoMatrixValue {
     NEITHER,
@@ -57,7 +94,8 @@ impl AndPseudoMatrixValue {
     }
 }
 
-fn increment_and_row(and_row: &mut Vec<AndPseudoMatrixValue>) {
+#[allow(clippy::needless_range_loop)]
+fn increment_and_row(and_row: &mut [AndPseudoMatrixValue]) {
     for i in 0..and_row.len() {
         if and_row[i] == AndPseudoMatrixValue::NEITHER {
             and_row[i] = AndPseudoMatrixValue::REQUIRED;
@@ -78,7 +116,8 @@ fn increment_and_row(and_row: &mut Vec<AndPseudoMatrixValue>) {
     panic!("AndPseudoMatrixValue vector overflow");
 }
 
-fn and_func_to_str(and_row: &Vec<AndPseudoMatrixValue>) -> String {
+#[allow(clippy::needless_range_loop)]
+fn and_func_to_str(and_row: &[AndPseudoMatrixValue]) -> String {
     let mut to_return = String::new();
 
     for i in 0..and_row.len() {
@@ -92,11 +131,12 @@ fn and_func_to_str(and_row: &Vec<AndPseudoMatrixValue>) -> String {
     to_return
 }
 
-fn bitvec2minterm_str(and_row: &Vec<bool>) -> String {
+#[allow(clippy::needless_range_loop)]
+fn bitvec2minterm_str(and_row: &[bool]) -> String {
     let mut to_return = String::new();
 
     for i in 0..and_row.len() {
-        if and_row[i] == true {
+        if and_row[i] {
             to_return.push_str(&format!("a{}", i));
         } else {
             to_return.push_str(&format!("a{}'", i));
@@ -107,9 +147,10 @@ fn bitvec2minterm_str(and_row: &Vec<bool>) -> String {
 }
 
 #[allow(dead_code)]
-fn bitvec2maxterm(and_row: &Vec<bool>) -> String {
+#[allow(clippy::needless_range_loop)]
+fn bitvec2maxterm(and_row: &[bool]) -> String {
     let mut to_return = String::new();
-    to_return.push_str("(");
+    to_return.push('(');
     let mut first_added = true;
 
     for i in 0..and_row.len() {
@@ -119,19 +160,19 @@ fn bitvec2maxterm(and_row: &Vec<bool>) -> String {
             to_return.push_str(" + ");
         }
         // The opposite of minterms by convention
-        if and_row[i] == false {
+        if !and_row[i] {
             to_return.push_str(&format!("a{}", i));
         } else {
             to_return.push_str(&format!("a{}'", i));
         }
     }
-    to_return.push_str(")");
+    to_return.push(')');
 
     to_return
 }
 
 // returns true if all input requirements described in the and row are satisfied, false otherwise
-fn compare_and_row(and_row: &Vec<AndPseudoMatrixValue>, input: &Vec<bool>) -> bool {
+fn compare_and_row(and_row: &[AndPseudoMatrixValue], input: &[bool]) -> bool {
     assert_eq!(and_row.len(), input.len());
     let mut to_return = false;
     for i in 0..and_row.len() {
@@ -162,7 +203,7 @@ fn compare_and_row(and_row: &Vec<AndPseudoMatrixValue>, input: &Vec<bool>) -> bo
 /// TODO: use the minimal representation, this one still has redundancies.
 /// For example: out0 = (a0) + (a0 + a1), it's equivalent to just out0 = (a0).
 /// This results in redudant different genotypes that result in equivalent fenotypes.
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash, Debug)]
 pub struct ProgrammableLogicArray {
     /// No need to store the input, pass it as a reference to calculate_output.
     /// The input is expected to be Vec<bool> with the same size as stored or it will panic.
@@ -190,7 +231,7 @@ impl ProgrammableLogicArray {
 
     pub fn new_null(in_size: usize, out_size: usize) -> ProgrammableLogicArray {
         let mut pla = ProgrammableLogicArray {
-            in_size: in_size,
+            in_size,
             or_matrix: Vec::with_capacity(out_size),
         };
         let or_column_size = ProgrammableLogicArray::calculate_or_column_size(in_size);
@@ -201,21 +242,21 @@ impl ProgrammableLogicArray {
         pla
     }
 
-    pub fn new_rand(in_size: usize, out_size: usize) -> ProgrammableLogicArray {
+    pub fn new_rand(in_size: usize, out_size: usize, rng: &mut dyn RngCore) -> ProgrammableLogicArray {
         let mut pla = ProgrammableLogicArray {
-            in_size: in_size,
+            in_size,
             or_matrix: Vec::with_capacity(out_size),
         };
         let or_column_size = ProgrammableLogicArray::calculate_or_column_size(in_size);
         for _i in 0..out_size {
-            pla.or_matrix.push(get_rand_bitvector(or_column_size));
+            pla.or_matrix.push(get_rand_bitvector(or_column_size, rng));
         }
         pla
     }
 
-    pub fn new_mutated(in_size: usize, out_size: usize, num_mutations: u32) -> ProgrammableLogicArray {
+    pub fn new_mutated(in_size: usize, out_size: usize, num_mutations: u32, rng: &mut dyn RngCore) -> ProgrammableLogicArray {
         let mut pla = ProgrammableLogicArray {
-            in_size: in_size,
+            in_size,
             or_matrix: Vec::with_capacity(out_size),
         };
         let or_column_size = ProgrammableLogicArray::calculate_or_column_size(in_size);
@@ -223,7 +264,7 @@ impl ProgrammableLogicArray {
             pla.or_matrix.push(get_null_bitvector(or_column_size));
         }
         for _i in 0..num_mutations {
-            pla.mutate();
+            pla.mutate(rng);
         }
         pla
     }
@@ -239,12 +280,29 @@ impl ProgrammableLogicArray {
 }
 
 impl Individual for ProgrammableLogicArray {
-    fn mutate(&mut self) {
-        let chosen_bit = rand::thread_rng().gen_range(0, self.or_matrix.len());
-        let chosen_output = rand::thread_rng().gen_range(0, self.or_matrix[0].len());
+    fn mutate(&mut self, rng: &mut dyn RngCore) {
+        let chosen_bit = rng.gen_range(0, self.or_matrix.len());
+        let chosen_output = rng.gen_range(0, self.or_matrix[0].len());
         self.or_matrix[chosen_bit][chosen_output] = !self.or_matrix[chosen_bit][chosen_output];
     }
 
+    /// Each bit of the or matrix is a gene.
+    fn num_genes(&self) -> usize {
+        self.or_matrix.len() * ProgrammableLogicArray::calculate_or_column_size(self.in_size)
+    }
+
+    fn mutate_gene(&mut self, gene: usize, _rng: &mut dyn RngCore) {
+        let or_column_size = ProgrammableLogicArray::calculate_or_column_size(self.in_size);
+        let bit = &mut self.or_matrix[gene / or_column_size][gene % or_column_size];
+        *bit = !*bit;
+    }
+
+    fn crossover(&self, other: &ProgrammableLogicArray, crossover: &Crossover, rng: &mut dyn RngCore) -> ProgrammableLogicArray {
+        assert_eq!(self.in_size, other.in_size);
+        genome_crossover(self, other, crossover, rng)
+    }
+
+    #[allow(clippy::needless_range_loop)]
     fn print(&self) {
         let out_size = self.or_matrix.len();
         let or_column_size = ProgrammableLogicArray::calculate_or_column_size(self.in_size);
@@ -267,7 +325,7 @@ impl Individual for ProgrammableLogicArray {
                     } else {
                         output_functions[i].push_str(" + ");
                     }
-                    output_functions[i].push_str(&format!("{}", and_function));
+                    output_functions[i].push_str(&and_function);
                 }
             }
 
@@ -281,7 +339,8 @@ impl Individual for ProgrammableLogicArray {
         }
     }
 
-    fn calculate_output(&self, input: &Vec<bool>) -> Vec<bool> {
+    #[allow(clippy::needless_range_loop)]
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
         assert_eq!(self.in_size, input.len());
         let or_column_size = ProgrammableLogicArray::calculate_or_column_size(self.in_size);
 
@@ -297,7 +356,7 @@ impl Individual for ProgrammableLogicArray {
         for j in 0..or_column_size {
 
             for i in 0..out_size {
-                if !output[i] && self.or_matrix[i][j] && compare_and_row(&and_matrix_row, &input) {
+                if !output[i] && self.or_matrix[i][j] && compare_and_row(&and_matrix_row, input) {
                     output[i] = true;
                 }
             }
@@ -319,9 +378,94 @@ impl Individual for ProgrammableLogicArray {
     }
 }
 
+/// The bits of a matrix as bit genes, row after row.
+fn bool_matrix_genes(matrix: &[Vec<bool>]) -> Vec<Gene> {
+    matrix.iter().flat_map(|row| row.iter().map(|&bit| Gene::Bit(bit))).collect()
+}
+
+/// Sets the bits of a matrix, row after row, from as many bit genes.
+fn set_bool_matrix_genes(matrix: &mut [Vec<bool>], genes: &[Gene]) {
+    assert_eq!(matrix.iter().map(|row| row.len()).sum::<usize>(), genes.len());
+    let mut gene = 0;
+    for row in matrix.iter_mut() {
+        for bit in row.iter_mut() {
+            *bit = genes[gene].bit();
+            gene += 1;
+        }
+    }
+}
+
+/// Writes a matrix of bools as its number of rows followed by the rows.
+fn save_bool_matrix(matrix: &[Vec<bool>], writer: &mut CheckpointWriter) {
+    writer.write(matrix.len());
+    for row in matrix.iter() {
+        writer.write_bools(row);
+    }
+}
+
+fn load_bool_matrix(reader: &mut CheckpointReader) -> io::Result< Vec< Vec<bool> > > {
+    let num_rows : usize = reader.read()?;
+    let mut matrix = Vec::with_capacity(num_rows);
+    for _ in 0..num_rows {
+        matrix.push(reader.read_bools()?);
+    }
+    Ok(matrix)
+}
+
+impl Checkpoint for ProgrammableLogicArray {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("ProgrammableLogicArray");
+        writer.write(self.in_size);
+        save_bool_matrix(&self.or_matrix, writer);
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<ProgrammableLogicArray> {
+        reader.expect("ProgrammableLogicArray")?;
+        Ok(ProgrammableLogicArray {
+            in_size: reader.read()?,
+            or_matrix: load_bool_matrix(reader)?,
+        })
+    }
+}
+
+impl Genome for ProgrammableLogicArray {
+    /// The bits of the or matrix, output after output.
+    fn genes(&self) -> Vec<Gene> {
+        bool_matrix_genes(&self.or_matrix)
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        set_bool_matrix_genes(&mut self.or_matrix, genes);
+    }
+}
+
+impl GenotypeDistance for ProgrammableLogicArray {
+    /// Number of different bits in the or matrix.
+    fn genotype_distance(&self, other: &ProgrammableLogicArray) -> f64 {
+        genome_distance(self, other)
+    }
+}
+
+impl Complexity for ProgrammableLogicArray {
+    /// Number of product terms (and rows) used by at least one output.
+    fn complexity(&self) -> usize {
+        let or_column_size = ProgrammableLogicArray::calculate_or_column_size(self.in_size);
+        let mut num_terms = 0;
+        for j in 0..or_column_size {
+            for i in 0..self.or_matrix.len() {
+                if self.or_matrix[i][j] {
+                    num_terms += 1;
+                    break;
+                }
+            }
+        }
+        num_terms
+    }
+}
+
 // returns true if all active bits in bitvector are also active in other or false otherwise
 #[allow(dead_code)]
-fn compare_and(bitvector: &Vec<bool>, other: &Vec<bool>) -> bool {
+fn compare_and(bitvector: &[bool], other: &[bool]) -> bool {
     assert_eq!(bitvector.len(), other.len());
     let mut to_return = false;
     for i in 0..bitvector.len() {
@@ -335,7 +479,7 @@ fn compare_and(bitvector: &Vec<bool>, other: &Vec<bool>) -> bool {
     to_return
 }
 
-fn eq_bitvector(bitvector: &Vec<bool>, other: &Vec<bool>) -> bool {
+fn eq_bitvector(bitvector: &[bool], other: &[bool]) -> bool {
     assert_eq!(bitvector.len(), other.len());
     for i in 0..bitvector.len() {
         if bitvector[i] != other[i] {
@@ -345,7 +489,8 @@ fn eq_bitvector(bitvector: &Vec<bool>, other: &Vec<bool>) -> bool {
     true
 }
 
-pub fn increment_bitvector(bitvector: &mut Vec<bool>) {
+#[allow(clippy::needless_range_loop)]
+pub fn increment_bitvector(bitvector: &mut [bool]) {
     for i in 0..bitvector.len() {
         if !bitvector[i] {
             bitvector[i] = true;
@@ -358,6 +503,24 @@ pub fn increment_bitvector(bitvector: &mut Vec<bool>) {
     panic!("bitvector overflow");
 }
 
+/// Every bitvector of the given size, starting from the null bitvector and following increment_bitvector.
+/// This is the order in which all the possible inputs of an individual are tested.
+pub fn all_bitvectors(vector_size: usize) -> impl Iterator<Item = Vec<bool>> {
+    let mut bitvector = get_null_bitvector(vector_size);
+    (0..2usize.pow(vector_size as u32)).map(move |j| {
+        if j > 0 {
+            increment_bitvector(&mut bitvector);
+        }
+        bitvector.clone()
+    })
+}
+
+/// Number of positions in which two bitvectors of the same len differ.
+pub fn hamming_distance(bitvector: &[bool], other: &[bool]) -> usize {
+    assert_eq!(bitvector.len(), other.len());
+    bitvector.iter().zip(other.iter()).filter(|(a, b)| a != b).count()
+}
+
 pub fn get_null_bitvector(vector_size: usize) -> Vec<bool> {
     let mut v: Vec<bool> = Vec::with_capacity(vector_size);
     for _i in 0..vector_size {
@@ -366,10 +529,10 @@ pub fn get_null_bitvector(vector_size: usize) -> Vec<bool> {
     v
 }
 
-pub fn get_rand_bitvector(vector_size: usize) -> Vec<bool> {
+pub fn get_rand_bitvector(vector_size: usize, rng: &mut dyn RngCore) -> Vec<bool> {
     let mut v: Vec<bool> = Vec::with_capacity(vector_size);
     for _i in 0..vector_size {
-        v.push(rand::thread_rng().gen_range(0, 2) > 0);
+        v.push(rng.gen_range(0, 2) > 0);
     }
     v
 }
@@ -383,7 +546,7 @@ pub fn print_bitvector(v: &[bool]) {
         };
         print!("{} ", bit_str);
     }
-    println!("");
+    println!();
 }
 
 fn print_limited_bitvector(v: &[bool], max: usize) {
@@ -398,7 +561,7 @@ fn print_limited_bitvector(v: &[bool], max: usize) {
             break;
         }
     }
-    println!("");
+    println!();
 }
 
 /// An individual implementing a single binary operation between two bitvectors of equal len.
@@ -413,11 +576,10 @@ fn print_limited_bitvector(v: &[bool], max: usize) {
 /// let bi_or = BinaryIndividual::new(BinOp::OR, 2);
 /// let bi_xor = BinaryIndividual::new(BinOp::XOR, 2);
 /// ```
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash, Debug)]
 pub struct BinaryIndividual {
     operation_type : BinOp,
     out_size : usize,
-    in_cardinality : usize,
 }
 
 impl BinaryIndividual {
@@ -426,13 +588,12 @@ impl BinaryIndividual {
         BinaryIndividual {
             operation_type,
             out_size : in_size / 2,
-            in_cardinality : 2usize.pow(in_size as u32),
         }
     }
 
-    /// input cardinality
-    fn in_cardinality(&self) -> usize {
-        self.in_cardinality
+    /// input cardinality, computed on demand since it doesn't fit in a usize for 64 input bits
+    fn in_cardinality(&self) -> u128 {
+        1u128 << self.input_size()
     }
 }
 
@@ -441,7 +602,6 @@ impl Clone for BinaryIndividual {
         BinaryIndividual {
             operation_type : self.operation_type.clone(),
             out_size : self.out_size,
-            in_cardinality : self.in_cardinality,
         }
     }
 }
@@ -449,11 +609,16 @@ impl Clone for BinaryIndividual {
 impl Individual for BinaryIndividual {
 
     /// unimplemented! This individual is not supposed to evolve, but only to be imitated by others using ImitationTask.
-    fn mutate(&mut self) {
+    fn mutate(&mut self, _rng: &mut dyn RngCore) {
         unimplemented!();
     }
 
-    fn calculate_output(&self, input: &Vec<bool>) -> Vec<bool> {
+    /// Panics: this individual is not supposed to evolve, but only to be imitated by others using ImitationTask.
+    fn crossover(&self, _other: &BinaryIndividual, _crossover: &Crossover, _rng: &mut dyn RngCore) -> BinaryIndividual {
+        panic!("BinaryIndividual is only supposed to be imitated, it can't be crossed over");
+    }
+
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
         assert_eq!(input.len(), self.input_size());
 
         let half_size = input.len() / 2;
@@ -493,6 +658,184 @@ impl Individual for BinaryIndividual {
     }
 }
 
+impl Checkpoint for BinaryIndividual {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("BinaryIndividual");
+        self.operation_type.save(writer);
+        writer.write(self.input_size());
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<BinaryIndividual> {
+        reader.expect("BinaryIndividual")?;
+        let operation_type = BinOp::load(reader)?;
+        let in_size : usize = reader.read()?;
+        Ok(BinaryIndividual::new(operation_type, in_size))
+    }
+}
+
+/// This individual doesn't evolve, so it has no genes.
+impl Genome for BinaryIndividual {
+    fn genes(&self) -> Vec<Gene> {
+        vec![]
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        assert!(genes.is_empty());
+    }
+}
+
+/// A sequential circuit adding two numbers fed one bit of each per step, least significant bits first.
+/// Its input is the two bits of a step and its output their sum bit, while the carry is kept as internal state.
+/// Like BinaryIndividual, it is only supposed to be imitated, see genetic::sequence::SequenceTask: mutating it or crossing it over panics.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::Individual;
+/// use tetani::digital::SerialAdder;
+/// let adder = SerialAdder::new();
+/// let mut state = adder.reset_state();
+/// // 1 + 1 = 10 in binary
+/// assert_eq!(vec![false], adder.step(&[true, true], &mut state));
+/// assert_eq!(vec![true], adder.step(&[false, false], &mut state));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SerialAdder;
+
+impl SerialAdder {
+    pub fn new() -> SerialAdder {
+        SerialAdder
+    }
+}
+
+impl Individual for SerialAdder {
+
+    /// Sum bit of a single step without carry.
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
+        let mut state = self.reset_state();
+        self.step(input, &mut state)
+    }
+
+    /// Panics: this individual is not supposed to evolve, but only to be imitated by others.
+    fn mutate(&mut self, _rng: &mut dyn RngCore) {
+        panic!("SerialAdder is only a reference to imitate and can't be mutated");
+    }
+
+    /// Panics: this individual is not supposed to evolve, but only to be imitated by others.
+    fn crossover(&self, _other: &SerialAdder, _crossover: &Crossover, _rng: &mut dyn RngCore) -> SerialAdder {
+        panic!("SerialAdder is only a reference to imitate and can't be crossed over");
+    }
+
+    fn print(&self) {
+        println!("SerialAdder");
+    }
+
+    fn input_size(&self) -> usize {
+        2
+    }
+
+    fn output_size(&self) -> usize {
+        1
+    }
+
+    /// The carry, initially zero.
+    fn reset_state(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn step(&self, input: &[bool], state: &mut Vec<bool>) -> Vec<bool> {
+        assert_eq!(input.len(), self.input_size());
+        let (a, b, carry) = (input[0], input[1], state[0]);
+        state[0] = (a && b) || (carry && (a != b));
+        vec![a ^ b ^ carry]
+    }
+}
+
+/// This individual doesn't evolve, so it has no genes.
+impl Genome for SerialAdder {
+    fn genes(&self) -> Vec<Gene> {
+        vec![]
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        assert!(genes.is_empty());
+    }
+}
+
+/// A sequential circuit reading one bit per step, whose output is true when the last bits read are the pattern.
+/// Occurrences of the pattern may overlap. Its internal state is the last bits read.
+/// Like BinaryIndividual, it is only supposed to be imitated, see genetic::sequence::SequenceTask: mutating it or crossing it over panics.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SequenceDetector {
+    pattern: Vec<bool>,
+}
+
+impl SequenceDetector {
+    pub fn new(pattern: Vec<bool>) -> SequenceDetector {
+        assert!(!pattern.is_empty());
+        SequenceDetector {
+            pattern,
+        }
+    }
+}
+
+impl Individual for SequenceDetector {
+
+    /// Whether a single step detects the pattern, which only happens for patterns of one bit.
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
+        let mut state = self.reset_state();
+        self.step(input, &mut state)
+    }
+
+    /// Panics: this individual is not supposed to evolve, but only to be imitated by others.
+    fn mutate(&mut self, _rng: &mut dyn RngCore) {
+        panic!("SequenceDetector is only a reference to imitate and can't be mutated");
+    }
+
+    /// Panics: this individual is not supposed to evolve, but only to be imitated by others.
+    fn crossover(&self, _other: &SequenceDetector, _crossover: &Crossover, _rng: &mut dyn RngCore) -> SequenceDetector {
+        panic!("SequenceDetector is only a reference to imitate and can't be crossed over");
+    }
+
+    fn print(&self) {
+        print!("SequenceDetector: pattern ");
+        print_bitvector(&self.pattern);
+    }
+
+    fn input_size(&self) -> usize {
+        1
+    }
+
+    fn output_size(&self) -> usize {
+        1
+    }
+
+    /// No bits read yet.
+    fn reset_state(&self) -> Vec<bool> {
+        vec![]
+    }
+
+    fn step(&self, input: &[bool], state: &mut Vec<bool>) -> Vec<bool> {
+        assert_eq!(input.len(), self.input_size());
+        state.push(input[0]);
+        if state.len() > self.pattern.len() {
+            state.remove(0);
+        }
+        vec![*state == self.pattern]
+    }
+}
+
+/// This individual doesn't evolve, so it has no genes.
+impl Genome for SequenceDetector {
+    fn genes(&self) -> Vec<Gene> {
+        vec![]
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        assert!(genes.is_empty());
+    }
+}
+
 /// https://en.wikipedia.org/wiki/Truth_table
 /// https://en.wikipedia.org/wiki/Canonical_normal_form
 ///
@@ -500,12 +843,13 @@ impl Individual for BinaryIndividual {
 ///
 /// ```
 /// use tetani::digital::TruthTable;
+/// let mut rng = rand::thread_rng();
 /// let tt_empt = TruthTable::new_empt(1);
 /// let tt_null = TruthTable::new_null(1, 1);
-/// let tt_rand = TruthTable::new_rand(1, 1);
-/// let tt_muta = TruthTable::new_muta(1, 1, 1);
+/// let tt_rand = TruthTable::new_rand(1, 1, &mut rng);
+/// let tt_muta = TruthTable::new_muta(1, 1, 1, &mut rng);
 /// ```
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash, Debug)]
 pub struct TruthTable {
     /// The inputs part of the table is reproduced programatically when needed by simply iterating the input bitvector.
     /// outputs: [[bool; 2^in_size]; out_size]
@@ -528,14 +872,14 @@ impl TruthTable {
 
     pub fn new_empt(in_size: usize) -> TruthTable {
         TruthTable {
-            in_size: in_size,
+            in_size,
             outputs: vec![],
         }
     }
 
     pub fn new_null(in_size: usize, out_size: usize) -> TruthTable {
         let mut tt = TruthTable {
-            in_size: in_size,
+            in_size,
             outputs: Vec::with_capacity(out_size),
         };
         let column_size = tt.get_input_space_cardinality();
@@ -545,21 +889,21 @@ impl TruthTable {
         tt
     }
 
-    pub fn new_rand(in_size: usize, out_size: usize) -> TruthTable {
+    pub fn new_rand(in_size: usize, out_size: usize, rng: &mut dyn RngCore) -> TruthTable {
         let mut tt = TruthTable {
-            in_size: in_size,
+            in_size,
             outputs: Vec::with_capacity(out_size),
         };
         let column_size = tt.get_input_space_cardinality();
         for _i in 0..out_size {
-            tt.outputs.push(get_rand_bitvector(column_size));
+            tt.outputs.push(get_rand_bitvector(column_size, rng));
         }
         tt
     }
 
-    pub fn new_muta(in_size: usize, out_size: usize, num_mutations: u32) -> TruthTable {
+    pub fn new_muta(in_size: usize, out_size: usize, num_mutations: u32, rng: &mut dyn RngCore) -> TruthTable {
         let mut tt = TruthTable {
-            in_size: in_size,
+            in_size,
             outputs: Vec::with_capacity(out_size),
         };
         let column_size = tt.get_input_space_cardinality();
@@ -567,7 +911,7 @@ impl TruthTable {
             tt.outputs.push(get_null_bitvector(column_size));
         }
         for _i in 0..num_mutations {
-            tt.mutate();
+            tt.mutate(rng);
         }
         tt
     }
@@ -578,12 +922,29 @@ impl TruthTable {
 }
 
 impl Individual for TruthTable {
-    fn mutate(&mut self) {
-        let chosen_output = rand::thread_rng().gen_range(0, self.outputs.len());
-        let chosen_input_combination = rand::thread_rng().gen_range(0, self.outputs[0].len());
+    fn mutate(&mut self, rng: &mut dyn RngCore) {
+        let chosen_output = rng.gen_range(0, self.outputs.len());
+        let chosen_input_combination = rng.gen_range(0, self.outputs[0].len());
         self.outputs[chosen_output][chosen_input_combination] = !self.outputs[chosen_output][chosen_input_combination];
     }
 
+    /// Each output bit of the table is a gene.
+    fn num_genes(&self) -> usize {
+        self.outputs.len() * self.get_input_space_cardinality()
+    }
+
+    fn mutate_gene(&mut self, gene: usize, _rng: &mut dyn RngCore) {
+        let column_size = self.get_input_space_cardinality();
+        let bit = &mut self.outputs[gene / column_size][gene % column_size];
+        *bit = !*bit;
+    }
+
+    fn crossover(&self, other: &TruthTable, crossover: &Crossover, rng: &mut dyn RngCore) -> TruthTable {
+        assert_eq!(self.in_size, other.in_size);
+        genome_crossover(self, other, crossover, rng)
+    }
+
+    #[allow(clippy::needless_range_loop)]
     fn print(&self) {
         let out_size = self.outputs.len();
         let column_size = self.get_input_space_cardinality();
@@ -605,7 +966,7 @@ impl Individual for TruthTable {
                     } else {
                         output_functions[i].push_str(" + ");
                     }
-                    output_functions[i].push_str(&format!("{}", minterm_str));
+                    output_functions[i].push_str(&minterm_str);
                 }
             }
 
@@ -619,7 +980,8 @@ impl Individual for TruthTable {
         }
     }
 
-    fn calculate_output(&self, input: &Vec<bool>) -> Vec<bool> {
+    #[allow(clippy::needless_range_loop)]
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
         assert_eq!(self.in_size, input.len());
         let out_size = self.outputs.len();
         let mut output : Vec<bool> = Vec::with_capacity(out_size);
@@ -632,7 +994,7 @@ impl Individual for TruthTable {
         for j in 0..column_size {
 
             for i in 0..out_size {
-                if !output[i] && self.outputs[i][j] && eq_bitvector(&minterm, &input) {
+                if !output[i] && self.outputs[i][j] && eq_bitvector(&minterm, input) {
                     output[i] = true;
                 }
             }
@@ -653,3 +1015,44 @@ impl Individual for TruthTable {
         self.in_size
     }
 }
+
+impl Checkpoint for TruthTable {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("TruthTable");
+        writer.write(self.in_size);
+        save_bool_matrix(&self.outputs, writer);
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<TruthTable> {
+        reader.expect("TruthTable")?;
+        Ok(TruthTable {
+            in_size: reader.read()?,
+            outputs: load_bool_matrix(reader)?,
+        })
+    }
+}
+
+impl Genome for TruthTable {
+    /// The output bits of the table, output after output.
+    fn genes(&self) -> Vec<Gene> {
+        bool_matrix_genes(&self.outputs)
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        set_bool_matrix_genes(&mut self.outputs, genes);
+    }
+}
+
+impl GenotypeDistance for TruthTable {
+    /// Number of different output bits in the table.
+    fn genotype_distance(&self, other: &TruthTable) -> f64 {
+        genome_distance(self, other)
+    }
+}
+
+impl Complexity for TruthTable {
+    /// Number of minterms, summed for all outputs.
+    fn complexity(&self) -> usize {
+        self.outputs.iter().map(|column| column.iter().filter(|&&bit| bit).count()).sum()
+    }
+}
@@ -1,29 +1,205 @@
 //! The genetic module is mostly to praise Darwin and Wallace. It implements Genetic Algorithms.
 
+use std::collections::HashMap;
+use std::io;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{
+    Hash,
+    Hasher,
+};
+
+use rand::{
+    Rng,
+    RngCore,
+    SeedableRng,
+};
+use rand_pcg::Pcg32;
+
+pub mod checkpoint;
+pub mod coevolution;
+pub mod dataset;
+pub mod diversity;
+pub mod genome;
+pub mod hall_of_fame;
+pub mod island;
+pub mod multiobjective;
+pub mod mutation;
+pub mod niching;
+pub mod novelty;
+pub mod observer;
+pub mod selection;
+pub mod sequence;
+pub mod termination;
+pub mod trajectory;
+
 use crate::digital::{
-    get_null_bitvector,
-    increment_bitvector,
+    all_bitvectors,
+    get_rand_bitvector,
     // print_bitvector,
 };
+use self::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use self::diversity::DiversityPolicy;
+use self::hall_of_fame::HallOfFame;
+use self::mutation::Mutation;
+use self::niching::{
+    Distance,
+    Phenotypic,
+};
+use self::observer::{
+    GenerationStats,
+    Observer,
+};
+use self::selection::{
+    Selection,
+    TruncationSelection,
+};
+use self::termination::{
+    check_criteria,
+    ProgressTracker,
+    Termination,
+    TerminationReason,
+};
 
 /// Individuals compete for fitness within a Population
 pub trait Individual {
-    fn calculate_output(&self, input: &Vec<bool>) -> Vec<bool>;
-    fn mutate(&mut self);
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool>;
+    fn mutate(&mut self, rng: &mut dyn RngCore);
+    /// Returns a child combining the genomes of self and other, which must have the same shape.
+    fn crossover(&self, other: &Self, crossover: &Crossover, rng: &mut dyn RngCore) -> Self where Self: Sized;
     fn print(&self);
     fn input_size(&self) -> usize;
     fn output_size(&self) -> usize;
+    /// Number of genes that mutate_gene can mutate. By default the whole genome is a single gene.
+    fn num_genes(&self) -> usize {
+        1
+    }
+    /// Mutates only the given gene, which must be lower than num_genes. By default it is the same as mutate.
+    fn mutate_gene(&mut self, _gene: usize, rng: &mut dyn RngCore) {
+        self.mutate(rng);
+    }
+    /// Internal state at the start of a sequence of inputs, see step. Stateless individuals have an empty state.
+    fn reset_state(&self) -> Vec<bool> {
+        vec![]
+    }
+    /// Output for the next input of a sequence, updating the state it was given, which starts as reset_state.
+    /// By default the individual is stateless: the state is ignored and the output is calculate_output.
+    fn step(&self, input: &[bool], _state: &mut Vec<bool>) -> Vec<bool> {
+        self.calculate_output(input)
+    }
+}
+
+/// Ways in which the genomes of two parents can be recombined into a child.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Crossover {
+    /// Genes before a random cut point come from the first parent and the rest from the second.
+    OnePoint,
+    /// Genes between two random cut points come from the second parent and the rest from the first.
+    TwoPoint,
+    /// Each gene comes from either parent with equal probability.
+    Uniform,
+}
+
+/// Recombines two gene sequences of equal length.
+pub fn crossover_genes<G: Clone>(genes: &[G], other: &[G], crossover: &Crossover, rng: &mut dyn RngCore) -> Vec<G> {
+    assert_eq!(genes.len(), other.len());
+    let num_genes = genes.len();
+    let mut child: Vec<G> = Vec::with_capacity(num_genes);
+    match crossover {
+        Crossover::OnePoint => {
+            let cut = rng.gen_range(0, num_genes + 1);
+            child.extend_from_slice(&genes[0..cut]);
+            child.extend_from_slice(&other[cut..num_genes]);
+        },
+        Crossover::TwoPoint => {
+            let mut first_cut = rng.gen_range(0, num_genes + 1);
+            let mut second_cut = rng.gen_range(0, num_genes + 1);
+            if first_cut > second_cut {
+                std::mem::swap(&mut first_cut, &mut second_cut);
+            }
+            child.extend_from_slice(&genes[0..first_cut]);
+            child.extend_from_slice(&other[first_cut..second_cut]);
+            child.extend_from_slice(&genes[second_cut..num_genes]);
+        },
+        Crossover::Uniform => {
+            for i in 0..num_genes {
+                if rng.gen_range(0, 2) > 0 {
+                    child.push(other[i].clone());
+                } else {
+                    child.push(genes[i].clone());
+                }
+            }
+        },
+    }
+    child
+}
+
+/// Recombines two matrices of the same shape as if they were a single gene sequence, row after row.
+pub fn crossover_matrix<G: Clone>(matrix: &[Vec<G>], other: &[Vec<G>], crossover: &Crossover, rng: &mut dyn RngCore) -> Vec<Vec<G>> {
+    assert_eq!(matrix.len(), other.len());
+    let genes: Vec<G> = matrix.concat();
+    let other_genes: Vec<G> = other.concat();
+    let child_genes = crossover_genes(&genes, &other_genes, crossover, rng);
+
+    let mut child: Vec<Vec<G>> = Vec::with_capacity(matrix.len());
+    let mut first_gene = 0;
+    for row in matrix.iter() {
+        child.push(child_genes[first_gene..first_gene + row.len()].to_vec());
+        first_gene += row.len();
+    }
+    child
+}
+
+/// Hash of the genome of an individual, equal genomes always have the same hash.
+pub fn genome_hash<I: Hash>(indi: &I) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    indi.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The behaviour of an individual: its outputs for all the possible inputs (see all_bitvectors), concatenated.
+pub fn behaviour(indi: &dyn Individual) -> Vec<bool> {
+    all_bitvectors(indi.input_size()).flat_map(|input| indi.calculate_output(&input)).collect()
 }
 
 /// Individuals are rated fitness for a given task
 pub trait Task {
-    fn calculate_fitness(&self, individual: &Individual) -> i32;
+    fn calculate_fitness(&self, individual: &dyn Individual) -> i32;
     fn max_fitness(&self) -> i32;
+
+    /// Errors of the individual on each test case of the task, lower is better, for selections that
+    /// compare individuals case by case (see selection::LexicaseSelection).
+    /// By default the whole task is a single case whose error is how far the individual is from the max fitness.
+    fn calculate_case_errors(&self, individual: &dyn Individual) -> Vec<i32> {
+        vec![self.max_fitness() - self.calculate_fitness(individual)]
+    }
+
+    /// Whether an individual with the given fitness solves the task, to stop learning (see termination::Termination::MaxFitness).
+    /// By default, whether it reaches the max fitness.
+    fn is_solved(&self, best_fitness: i32) -> bool {
+        best_fitness >= self.max_fitness()
+    }
+
+    /// Called by Population before producing each generation, for tasks that change over time.
+    /// Returns whether individuals already rated may now get a different fitness, so that the population is rated again.
+    fn prepare_generation(&mut self, _rng: &mut dyn RngCore) -> bool {
+        false
+    }
+
+    /// Called by Population before prepare_generation with its individuals, best first, for tasks that learn from them
+    /// (see novelty::NoveltyTask). Returns whether individuals already rated may now get a different fitness, as prepare_generation.
+    fn observe_population(&mut self, _population: &[&dyn Individual]) -> bool {
+        false
+    }
 }
 
-struct RatedIndividual<I: Individual> {
-    indi: I,
-    fitness: i32,
+/// An individual together with its fitness for the task of its Population
+pub struct RatedIndividual<I: Individual> {
+    pub indi: I,
+    pub fitness: i32,
 }
 
 impl<I> RatedIndividual<I>
@@ -34,29 +210,107 @@ impl<I> RatedIndividual<I>
     }
 }
 
+/// How the offspring of each generation replace the individuals of a Population once it is full.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Replacement {
+    /// Each child replaces the worst individual of the population, even if the child is worse.
+    SteadyState,
+    /// The offspring replace the whole population except for its best elites individuals.
+    /// This is normally used with as many offspring as the capacity of the population.
+    Generational { elites: usize },
+    /// Elitist: the best individuals among the current population (mu) and the offspring (lambda) survive.
+    /// This is the default.
+    MuPlusLambda,
+    /// Only the best offspring (lambda) survive, the current population (mu) is discarded.
+    /// This is normally used with more offspring than the capacity of the population.
+    MuCommaLambda,
+    /// Deterministic crowding: each child competes only with the closest of its parents (see Population::set_distance),
+    /// and replaces it if the child is at least as fit. The size of the population doesn't change.
+    DeterministicCrowding,
+}
+
 /// A population groups individuals and orders them by their fitness for a given task.
+/// The number of individuals is bounded by the capacity of the population.
 pub struct Population<I: Individual, T: Task> {
     pub task: T,
-    /// TODO This list should be ordered by fitness
-    /// TODO Some data struct ordered by fitness (repeated values are allowed) should replace Vec
+    /// Ordered by fitness, best first. Among individuals with the same fitness, the oldest go first.
     pop: Vec< RatedIndividual<I> >,
     /// Storing a list of unrated individuals temporarely is a perfectly valid way to prepare unrated individuals to be rated in parallel.
     unrated_pop: Vec<I>,
-    /// Number of individuals selected by truncation per generation
-    num_selection_truncation: usize,
+    /// Number of threads used to rate individuals
+    num_threads: usize,
+    /// Maximum number of rated individuals
+    capacity: usize,
+    replacement: Replacement,
+    /// Strategy used to choose the parents of each generation
+    selection: Box<dyn Selection<I>>,
+    /// Distance between individuals used by deterministic crowding
+    distance: Box<dyn Distance<I>>,
+    /// Number of individuals selected per generation
+    num_selected: usize,
+    /// Number of children produced per generation, by default one per selected individual
+    num_offspring: Option<usize>,
+    /// Probability of a selected individual being recombined with another selected individual before mutation
+    crossover_rate: f64,
+    crossover: Crossover,
+    /// Applied to every child after crossover
+    mutation: Mutation,
+    /// Number of generations produced so far
+    generation: usize,
+    /// Number of times the task has calculated the fitness of an individual
+    num_evaluations: usize,
+    /// Fitnesses already calculated, by genome hash. Disabled by default.
+    fitness_cache: Option< HashMap<u64, i32> >,
+    /// Number of individuals whose fitness was found in the cache
+    cache_hits: usize,
+    /// Number of individuals whose fitness wasn't found in the cache
+    cache_misses: usize,
+    /// Errors on each test case of the individuals of the population, by genome hash, for selections that use them
+    case_errors: HashMap< u64, Vec<i32> >,
+    /// Best individuals ever rated, see enable_hall_of_fame. Disabled by default.
+    hall_of_fame: Option< HallOfFame<I> >,
+    /// Number of best members of the hall of fame put back into the population after every generation
+    hall_of_fame_elites: usize,
+    /// Reacts to low diversity after every generation, see diversity::DiversityPolicy. None by default.
+    diversity_policy: Option< DiversityPolicy<I> >,
+    /// Notified after every generation
+    observers: Vec< Box<dyn Observer> >,
+    /// Source of randomness for selection, crossover and mutation
+    rng: Pcg32,
 }
 
 impl<I, T> Population<I, T>
-    where I: Individual + 'static + Clone, T: Task {
+    where I: Individual + 'static + Clone + Send + Sync + Hash, T: Task + Sync {
 
     pub fn new(task: T, capacity: usize) -> Population<I, T> {
+        assert!(capacity > 0);
         let pop : Vec< RatedIndividual<I> > = Vec::with_capacity(capacity);
         let unrated_pop : Vec<I> = Vec::with_capacity(capacity);
         Population{
             task,
             pop,
             unrated_pop,
-            num_selection_truncation: 1,
+            num_threads: 1,
+            capacity,
+            replacement: Replacement::MuPlusLambda,
+            selection: Box::new(TruncationSelection),
+            distance: Box::new(Phenotypic),
+            num_selected: 1,
+            num_offspring: None,
+            crossover_rate: 0.0,
+            crossover: Crossover::OnePoint,
+            mutation: Mutation::Single,
+            generation: 0,
+            num_evaluations: 0,
+            fitness_cache: None,
+            cache_hits: 0,
+            cache_misses: 0,
+            case_errors: HashMap::new(),
+            hall_of_fame: None,
+            hall_of_fame_elites: 0,
+            diversity_policy: None,
+            observers: vec![],
+            rng: Pcg32::from_rng(rand::thread_rng()).unwrap(),
         }
     }
 
@@ -64,8 +318,132 @@ impl<I, T> Population<I, T>
         self.pop.len()
     }
 
-    pub fn set_num_selection_truncation(&mut self, num: usize) {
-        self.num_selection_truncation = num;
+    pub fn is_empty(&self) -> bool {
+        self.pop.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations
+    }
+
+    /// Runs with the same seed and the same initial individuals produce identical populations.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Pcg32::seed_from_u64(seed);
+    }
+
+    /// The random number generator of the population, for example to create its initial individuals.
+    pub fn rng(&mut self) -> &mut Pcg32 {
+        &mut self.rng
+    }
+
+    /// Both the unrated individuals and the offspring of each generation are rated by num_threads threads.
+    pub fn set_num_threads(&mut self, num_threads: usize) {
+        assert!(num_threads > 0);
+        self.num_threads = num_threads;
+    }
+
+    /// Individuals with the same genome as a previously rated individual get the same fitness without being evaluated again.
+    /// Note that different genomes with the same hash would be considered equal, although that should be very unlikely.
+    /// The task must always rate equal individuals with the same fitness.
+    pub fn enable_fitness_cache(&mut self) {
+        if self.fitness_cache.is_none() {
+            self.fitness_cache = Some(HashMap::new());
+        }
+    }
+
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits
+    }
+
+    pub fn cache_misses(&self) -> usize {
+        self.cache_misses
+    }
+
+    /// Keeps the capacity best individuals with different phenotypes ever rated, starting with the current population.
+    /// See HallOfFame.
+    pub fn enable_hall_of_fame(&mut self, capacity: usize) {
+        if self.hall_of_fame.is_none() {
+            let mut hall_of_fame = HallOfFame::new(capacity);
+            hall_of_fame.offer_all(&self.pop);
+            self.hall_of_fame = Some(hall_of_fame);
+        }
+    }
+
+    pub fn hall_of_fame(&self) -> Option<&HallOfFame<I>> {
+        self.hall_of_fame.as_ref()
+    }
+
+    /// After every generation, the num best members of the hall of fame that are not in the population are put back into it,
+    /// with the fitness they had when they entered the hall of fame. Only has an effect with the hall of fame enabled.
+    pub fn set_hall_of_fame_elites(&mut self, num: usize) {
+        self.hall_of_fame_elites = num;
+    }
+
+    pub fn set_replacement(&mut self, replacement: Replacement) {
+        self.replacement = replacement;
+    }
+
+    pub fn set_num_selected(&mut self, num: usize) {
+        assert!(num > 0);
+        self.num_selected = num;
+    }
+
+    pub fn set_num_offspring(&mut self, num: usize) {
+        assert!(num > 0);
+        self.num_offspring = Some(num);
+    }
+
+    pub fn set_selection<S: Selection<I> + 'static>(&mut self, selection: S) {
+        self.selection = Box::new(selection);
+    }
+
+    /// Distance used by deterministic crowding to find the closest parent of each child. Phenotypic by default.
+    pub fn set_distance<D: Distance<I> + 'static>(&mut self, distance: D) {
+        self.distance = Box::new(distance);
+    }
+
+    pub fn set_crossover_rate(&mut self, crossover_rate: f64) {
+        assert!((0.0..=1.0).contains(&crossover_rate));
+        self.crossover_rate = crossover_rate;
+    }
+
+    pub fn set_crossover(&mut self, crossover: Crossover) {
+        self.crossover = crossover;
+    }
+
+    /// Panics if the mutation is not valid, see Mutation::is_valid. Ends the current mutation boost of the diversity policy, if any.
+    pub fn set_mutation(&mut self, mutation: Mutation) {
+        assert!(mutation.is_valid(), "invalid mutation: {:?}", mutation);
+        self.end_mutation_boost();
+        self.mutation = mutation;
+    }
+
+    /// The mutation applied to children, including the current state of adaptive mutations.
+    pub fn mutation(&self) -> &Mutation {
+        &self.mutation
+    }
+
+    /// The observer is notified after every generation with the stats of the population.
+    pub fn add_observer<O: Observer + 'static>(&mut self, observer: O) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Stats of the current population, which must not be empty.
+    pub fn stats(&self) -> GenerationStats {
+        GenerationStats::new(&self.pop, self.generation, self.num_evaluations)
+    }
+
+    /// All the rated individuals, best first.
+    pub fn individuals(&self) -> &[RatedIndividual<I>] {
+        &self.pop
     }
 
     pub fn best_fitness(&self) -> i32 {
@@ -76,55 +454,379 @@ impl<I, T> Population<I, T>
         &self.pop[0].indi
     }
 
+    /// Inserts keeping the population ordered, without removing anyone.
+    fn insert_rated_individual(&mut self, indi: RatedIndividual<I>) {
+        let position = self.pop.partition_point(|rated| rated.fitness >= indi.fitness);
+        self.pop.insert(position, indi);
+    }
+
+    /// Inserts keeping the population ordered and removes the worst individual if the capacity is exceeded.
     fn add_rated_individual(&mut self, indi: RatedIndividual<I>) {
-        self.pop.push(indi);
-        self.pop.sort_by_key(|indi| std::cmp::Reverse(indi.fitness));
+        self.insert_rated_individual(indi);
+        self.pop.truncate(self.capacity);
     }
 
     pub fn add_unrated_individual(&mut self, indi: I) {
         self.unrated_pop.push(indi);
     }
 
+    fn rate_individual(&mut self, indi: I) -> RatedIndividual<I> {
+        self.rate_individuals(vec![indi]).pop().unwrap()
+    }
+
+    /// Applies the task to each individual splitting them between the configured number of threads, preserving their order.
+    fn calculate_in_threads<R, F>(&self, indis: &[&I], calculate: F) -> Vec<R>
+        where R: Send, F: Fn(&T, &I) -> R + Sync {
+
+        let num_threads = self.num_threads.min(indis.len());
+        if num_threads <= 1 {
+            return indis.iter().map(|indi| calculate(&self.task, *indi)).collect();
+        }
+
+        let task = &self.task;
+        let calculate = &calculate;
+        let chunk_size = indis.len().div_ceil(num_threads);
+        std::thread::scope(|scope| {
+            let handles : Vec<_> = indis.chunks(chunk_size).map(|chunk| {
+                scope.spawn(move || {
+                    chunk.iter().map(|indi| calculate(task, *indi)).collect::<Vec<R>>()
+                })
+            }).collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        })
+    }
+
+    /// Calculates the fitness of each individual in parallel, preserving their order.
+    fn calculate_fitnesses(&self, indis: &[&I]) -> Vec<i32> {
+        self.calculate_in_threads(indis, |task, indi| task.calculate_fitness(indi))
+    }
+
+    /// Errors on each test case of every individual of the population, in the same order.
+    /// Only individuals new to the population are evaluated, and individuals no longer in it are forgotten.
+    fn population_case_errors(&mut self) -> Vec< Vec<i32> > {
+        let hashes : Vec<u64> = self.pop.iter().map(|rated| genome_hash(&rated.indi)).collect();
+        let mut missing_hashes : Vec<u64> = vec![];
+        let mut missing : Vec<&I> = vec![];
+        for (k, &hash) in hashes.iter().enumerate() {
+            if !self.case_errors.contains_key(&hash) && !missing_hashes.contains(&hash) {
+                missing_hashes.push(hash);
+                missing.push(&self.pop[k].indi);
+            }
+        }
+        let missing_errors = self.calculate_in_threads(&missing, |task, indi| task.calculate_case_errors(indi));
+
+        let mut case_errors : HashMap< u64, Vec<i32> > = missing_hashes.into_iter().zip(missing_errors).collect();
+        for &hash in hashes.iter() {
+            if let Some(errors) = self.case_errors.remove(&hash) {
+                case_errors.insert(hash, errors);
+            }
+        }
+        let errors = hashes.iter().map(|hash| case_errors[hash].clone()).collect();
+        self.case_errors = case_errors;
+        errors
+    }
+
+    /// Rates the individuals preserving their order.
+    /// With the fitness cache enabled, only individuals with genomes not seen before are evaluated.
+    fn rate_individuals(&mut self, indis: Vec<I>) -> Vec< RatedIndividual<I> > {
+        let num_indis = indis.len();
+        let mut cached_fitnesses : Vec< Option<i32> > = vec![None; num_indis];
+        // Position within to_evaluate of each individual whose fitness isn't cached
+        let mut evaluated_positions : Vec<usize> = vec![0; num_indis];
+        let mut to_evaluate : Vec<&I> = Vec::with_capacity(num_indis);
+        let mut evaluated_hashes : Vec<u64> = vec![];
+
+        // Equal genomes within the same batch are only evaluated once
+        let mut pending : HashMap<u64, usize> = HashMap::new();
+        for (k, indi) in indis.iter().enumerate() {
+            if let Some(ref fitness_cache) = self.fitness_cache {
+                let hash = genome_hash(indi);
+                if let Some(&fitness) = fitness_cache.get(&hash) {
+                    cached_fitnesses[k] = Some(fitness);
+                    self.cache_hits += 1;
+                    continue;
+                }
+                if let Some(&position) = pending.get(&hash) {
+                    evaluated_positions[k] = position;
+                    self.cache_hits += 1;
+                    continue;
+                }
+                pending.insert(hash, to_evaluate.len());
+                evaluated_hashes.push(hash);
+                self.cache_misses += 1;
+            }
+            evaluated_positions[k] = to_evaluate.len();
+            to_evaluate.push(indi);
+        }
+
+        let fitnesses = self.calculate_fitnesses(&to_evaluate);
+        self.num_evaluations += to_evaluate.len();
+        if let Some(ref mut fitness_cache) = self.fitness_cache {
+            for (hash, &fitness) in evaluated_hashes.into_iter().zip(fitnesses.iter()) {
+                fitness_cache.insert(hash, fitness);
+            }
+        }
+
+        let mut rated = Vec::with_capacity(num_indis);
+        for (k, indi) in indis.into_iter().enumerate() {
+            let fitness = match cached_fitnesses[k] {
+                Some(fitness) => fitness,
+                None => fitnesses[evaluated_positions[k]],
+            };
+            rated.push(RatedIndividual{indi, fitness});
+        }
+        rated
+    }
+
     pub fn add_and_rate_individual(&mut self, indi: I) {
-        let fitness = self.task.calculate_fitness(&indi);
-        self.add_rated_individual(RatedIndividual{indi, fitness});
+        let rated = self.rate_individual(indi);
+        self.offer_to_hall_of_fame(std::slice::from_ref(&rated));
+        self.add_rated_individual(rated);
+    }
+
+    fn offer_to_hall_of_fame(&mut self, rated_indis: &[RatedIndividual<I>]) {
+        if let Some(ref mut hall_of_fame) = self.hall_of_fame {
+            hall_of_fame.offer_all(rated_indis);
+        }
+    }
+
+    /// Puts back the best members of the hall of fame missing from the population, see set_hall_of_fame_elites.
+    fn restore_hall_of_fame_elites(&mut self) {
+        let elites = match self.hall_of_fame {
+            Some(ref hall_of_fame) if self.hall_of_fame_elites > 0 => hall_of_fame.elites(self.hall_of_fame_elites),
+            _ => return,
+        };
+        let hashes : Vec<u64> = self.pop.iter().map(|rated| genome_hash(&rated.indi)).collect();
+        for elite in elites {
+            if !hashes.contains(&genome_hash(&elite.indi)) {
+                self.add_rated_individual(elite);
+            }
+        }
+    }
+
+    /// Returns the positions of the selected individuals in the population, and copies of them.
+    fn select(&mut self) -> (Vec<usize>, Vec< RatedIndividual<I> >) {
+        let positions = if self.selection.uses_case_errors() {
+            let case_errors = self.population_case_errors();
+            self.selection.select_by_cases(&self.pop, &case_errors, self.num_selected, &mut self.rng)
+        } else {
+            self.selection.select(&self.pop, self.num_selected, &mut self.rng)
+        };
+        let mut selected = Vec::with_capacity(positions.len());
+        for &position in positions.iter() {
+            selected.push(RatedIndividual {
+                indi: self.pop[position].indi.clone(),
+                fitness: self.pop[position].fitness,
+            });
+        }
+        (positions, selected)
+    }
+
+    /// Produces the offspring by crossover (with the configured rate) and mutation of the selected individuals.
+    /// The parent of child k is selected[k % selected.len()], possibly recombined with another selected individual.
+    /// Also returns the parents of each child, as indices in selected.
+    fn reproduce(&mut self, selected: &[RatedIndividual<I>]) -> (Vec<I>, Vec< Vec<usize> >) {
+        let num_selected = selected.len();
+        let num_offspring = self.num_offspring.unwrap_or(num_selected);
+        let mut offspring = Vec::with_capacity(num_offspring);
+        let mut parents = Vec::with_capacity(num_offspring);
+        for k in 0..num_offspring {
+            let i = k % num_selected;
+            let mut child = if num_selected > 1 && self.rng.gen_bool(self.crossover_rate) {
+                // Choose a partner among the other selected individuals
+                let mut partner = self.rng.gen_range(0, num_selected - 1);
+                if partner >= i {
+                    partner += 1;
+                }
+                parents.push(vec![i, partner]);
+                selected[i].indi.crossover(&selected[partner].indi, &self.crossover, &mut self.rng)
+            } else {
+                parents.push(vec![i]);
+                selected[i].indi.clone()
+            };
+            self.mutation.mutate(&mut child, &mut self.rng);
+            offspring.push(child);
+        }
+        (offspring, parents)
+    }
+
+    /// Rated offspring take the place of individuals in the population according to the replacement policy.
+    /// parents contains the positions in the population of the parents of each child.
+    fn replace(&mut self, offspring: Vec< RatedIndividual<I> >, parents: &[Vec<usize>]) {
+        match self.replacement {
+            Replacement::SteadyState => {
+                for child in offspring {
+                    if self.pop.len() >= self.capacity {
+                        self.pop.pop();
+                    }
+                    self.insert_rated_individual(child);
+                }
+            },
+            Replacement::Generational { elites } => {
+                self.pop.truncate(elites);
+                for child in offspring {
+                    self.add_rated_individual(child);
+                }
+            },
+            Replacement::MuPlusLambda => {
+                for child in offspring {
+                    self.add_rated_individual(child);
+                }
+            },
+            Replacement::MuCommaLambda => {
+                self.pop.clear();
+                for child in offspring {
+                    self.add_rated_individual(child);
+                }
+            },
+            Replacement::DeterministicCrowding => {
+                for (child, child_parents) in offspring.into_iter().zip(parents.iter()) {
+                    let mut closest = child_parents[0];
+                    let mut closest_distance = self.distance.distance(&child.indi, &self.pop[closest].indi);
+                    for &parent in child_parents[1..].iter() {
+                        let distance = self.distance.distance(&child.indi, &self.pop[parent].indi);
+                        if distance < closest_distance {
+                            closest = parent;
+                            closest_distance = distance;
+                        }
+                    }
+                    if child.fitness >= self.pop[closest].fitness {
+                        self.pop[closest] = child;
+                    }
+                }
+                // The sort is stable, so individuals with the same fitness keep their order
+                self.pop.sort_by_key(|rated| std::cmp::Reverse(rated.fitness));
+            },
+        }
     }
 
-    fn selection_truncation(&self) -> Vec<I> {
-        assert!(self.pop.len() >= self.num_selection_truncation);
-        let mut selected = Vec::with_capacity(self.num_selection_truncation);
-        for i in 0..self.num_selection_truncation {
-            selected.push(self.pop[i].indi.clone());
+    /// Adds individuals rated by another population, like migrants between islands, the way offspring are added:
+    /// they are offered to the hall of fame and then replace individuals according to the replacement policy.
+    /// Since they have no parents here, DeterministicCrowding compares each of them with the closest individual of the population,
+    /// and Generational and MuCommaLambda, which would discard the population, replace the worst individuals like SteadyState.
+    fn add_immigrants(&mut self, immigrants: Vec< RatedIndividual<I> >) {
+        self.offer_to_hall_of_fame(&immigrants);
+        match self.replacement {
+            Replacement::SteadyState | Replacement::MuPlusLambda | Replacement::DeterministicCrowding => {
+                let all_positions : Vec<usize> = (0..self.pop.len()).collect();
+                let parents = vec![all_positions; immigrants.len()];
+                self.replace(immigrants, &parents);
+            },
+            Replacement::Generational { .. } | Replacement::MuCommaLambda => {
+                for immigrant in immigrants {
+                    if self.pop.len() >= self.capacity {
+                        self.pop.pop();
+                    }
+                    self.insert_rated_individual(immigrant);
+                }
+            },
         }
-        selected
     }
 
     pub fn next_generation(&mut self) {
-        let mut selected = self.selection_truncation();
-        for i in 0..selected.len() {
-            selected[i].mutate();
-            // TODO This additional clone is redundant
-            self.add_and_rate_individual(selected[i].clone());
+        let population : Vec<&dyn Individual> = self.pop.iter().map(|rated| &rated.indi as &dyn Individual).collect();
+        let observed_changes = self.task.observe_population(&population);
+        if self.task.prepare_generation(&mut self.rng) || observed_changes {
+            self.rerate_population();
+        }
+        let (positions, selected) = self.select();
+        let (offspring, parents) = self.reproduce(&selected);
+        let rated_offspring = self.rate_individuals(offspring);
+        self.offer_to_hall_of_fame(&rated_offspring);
+        let mut num_successes = 0;
+        for k in 0..rated_offspring.len() {
+            if rated_offspring[k].fitness > selected[k % selected.len()].fitness {
+                num_successes += 1;
+            }
         }
+        let num_genes = selected[0].indi.num_genes();
+        self.mutation.adapt(num_successes, rated_offspring.len(), num_genes);
+        let parent_positions : Vec< Vec<usize> > = parents.iter()
+            .map(|child_parents| child_parents.iter().map(|&i| positions[i]).collect())
+            .collect();
+        self.replace(rated_offspring, &parent_positions);
+        self.restore_hall_of_fame_elites();
+        self.apply_diversity_policy();
+        self.generation += 1;
+        if !self.observers.is_empty() {
+            let stats = self.stats();
+            for observer in self.observers.iter_mut() {
+                observer.observe(&stats);
+            }
+        }
+    }
+
+    /// Learns until reaching the max fitness of the task or the given generation.
+    pub fn learn_task(&mut self, max_generation: usize) -> TerminationReason {
+        self.learn_task_until(&[Termination::MaxFitness, Termination::MaxGenerations(max_generation)])
     }
 
-    pub fn learn_task(&mut self, max_generation: usize) {
-        while self.best_fitness() < self.task.max_fitness() && self.pop.len() < max_generation {
+    /// Learns until any of the criteria holds, and returns the reason of the first one that holds.
+    /// The criteria are checked before each generation, so no generation is produced if any already holds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tetani::digital::BinOp;
+    /// use tetani::digital::BinaryIndividual;
+    /// use tetani::digital::TruthTable;
+    /// use tetani::genetic::ImitationTask;
+    /// use tetani::genetic::Population;
+    /// use tetani::genetic::termination::Termination;
+    /// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+    /// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 10);
+    /// pop.add_unrated_individual(TruthTable::new_null(2, 1));
+    /// pop.rate_unrated_individuals();
+    /// let reason = pop.learn_task_until(&[
+    ///     Termination::MaxFitness,
+    ///     Termination::MaxEvaluations(10000),
+    ///     Termination::Stagnation(500),
+    ///     Termination::TimeBudget(Duration::from_secs(10)),
+    ///     Termination::Predicate(Box::new(|progress| progress.best_fitness >= 3 && progress.generation >= 100)),
+    /// ]);
+    /// println!("Stopped because of {:?}", reason);
+    /// ```
+    pub fn learn_task_until(&mut self, criteria: &[Termination]) -> TerminationReason {
+        assert!(!criteria.is_empty());
+        let mut tracker = ProgressTracker::new(self.best_fitness());
+        loop {
+            let progress = tracker.progress(self.generation, self.num_evaluations, self.best_fitness(), self.task.max_fitness(), self.task.is_solved(self.best_fitness()));
+            if let Some(reason) = check_criteria(criteria, &progress) {
+                return reason;
+            }
             self.next_generation();
-            // self.print();
+            tracker.update(self.best_fitness());
+        }
+    }
+
+    /// Rates again every individual of the population and of the hall of fame, forgetting the cached fitnesses.
+    /// Needed after changing the task in a way that changes the fitness of individuals, for example their opponents in co-evolution.
+    pub fn rerate_population(&mut self) {
+        if let Some(ref mut fitness_cache) = self.fitness_cache {
+            fitness_cache.clear();
+        }
+        self.case_errors.clear();
+        let indis : Vec<I> = std::mem::take(&mut self.pop).into_iter().map(|rated| rated.indi).collect();
+        self.pop = self.rate_individuals(indis);
+        self.pop.sort_by_key(|rated| std::cmp::Reverse(rated.fitness));
+
+        if let Some(hall_of_fame) = self.hall_of_fame.take() {
+            let members : Vec<I> = hall_of_fame.members().iter().map(|rated| rated.indi.clone()).collect();
+            let mut rerated_hall_of_fame = HallOfFame::new(hall_of_fame.capacity());
+            rerated_hall_of_fame.offer_all(&self.rate_individuals(members));
+            rerated_hall_of_fame.offer_all(&self.pop);
+            self.hall_of_fame = Some(rerated_hall_of_fame);
         }
     }
 
     pub fn rate_unrated_individuals(&mut self) {
-        for indi in self.unrated_pop.iter() {
-            let fitness = self.task.calculate_fitness(indi);
-            self.pop.push(RatedIndividual{
-                indi: indi.clone(),
-                fitness,
-            });
+        let unrated_pop = std::mem::take(&mut self.unrated_pop);
+        let rated_indis = self.rate_individuals(unrated_pop);
+        self.offer_to_hall_of_fame(&rated_indis);
+        for rated in rated_indis {
+            self.add_rated_individual(rated);
         }
-        self.pop.sort_by_key(|indi| std::cmp::Reverse(indi.fitness));
-        self.unrated_pop.clear();
     }
 
     pub fn print(&self) {
@@ -134,7 +836,7 @@ impl<I, T> Population<I, T>
     }
 }
 
-fn calculate_fitness_result(result: &Vec<bool>, v_tested: &Vec<bool>) -> i32 {
+fn calculate_fitness_result(result: &[bool], v_tested: &[bool]) -> i32 {
     assert_eq!(result.len(), v_tested.len());
     let mut fitness = 0;
     for i in 0..result.len() {
@@ -145,9 +847,125 @@ fn calculate_fitness_result(result: &Vec<bool>, v_tested: &Vec<bool>) -> i32 {
     fitness
 }
 
+/// Inputs on which ImitationTask compares the imitated individual with the rated ones.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Sampling {
+    /// All the 2^in_size possible inputs. This is the default.
+    Exhaustive,
+    /// The same num_samples random inputs for the whole evolution, drawn from the seed.
+    Fixed { num_samples: usize, seed: u64 },
+    /// num_samples random inputs drawn from the seed, and drawn again before each generation from the random number generator of the population.
+    PerGeneration { num_samples: usize, seed: u64 },
+}
+
+/// How ImitationTask scores the output of an individual for an input, compared with the output of the imitated individual.
+/// Outputs are little endian: the first bit is the least significant.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Scoring {
+    /// One point per output bit equal to the imitated one (Hamming similarity). This is the default.
+    Hamming,
+    /// One point per input whose whole output is equal to the imitated one.
+    ExactMatch,
+    /// Each output bit equal to the imitated one scores its weight, given for each output bit.
+    Weighted(Vec<i32>),
+    /// Outputs are read as unsigned integers, and score 2^output_size - 1 minus their distance to the imitated one.
+    /// Note that close numbers may differ in many bits (like 3 and 4), which misleads mutations of few bits.
+    NumericDistance,
+}
+
+impl Scoring {
+    /// Weighted scoring where each output bit is worth twice the previous one, so the most significant bit matters most.
+    pub fn binary_weights(out_size: usize) -> Scoring {
+        assert!(out_size < 31);
+        Scoring::Weighted((0..out_size).map(|i| 1 << i).collect())
+    }
+
+    /// Score of the output for an input whose expected output is the given one.
+    pub fn score(&self, expected: &[bool], output: &[bool]) -> i32 {
+        assert_eq!(expected.len(), output.len());
+        match *self {
+            Scoring::Hamming => calculate_fitness_result(expected, output),
+            Scoring::ExactMatch => if expected == output { 1 } else { 0 },
+            Scoring::Weighted(ref weights) => {
+                (0..expected.len()).filter(|&i| expected[i] == output[i]).map(|i| weights[i]).sum()
+            },
+            Scoring::NumericDistance => {
+                self.max_score(expected.len()) - (bitvector_to_integer(expected) - bitvector_to_integer(output)).abs()
+            },
+        }
+    }
+
+    /// Score of a perfect output of the given size.
+    pub fn max_score(&self, out_size: usize) -> i32 {
+        match *self {
+            Scoring::Hamming => out_size as i32,
+            Scoring::ExactMatch => 1,
+            Scoring::Weighted(ref weights) => weights.iter().sum(),
+            Scoring::NumericDistance => (1 << out_size) - 1,
+        }
+    }
+
+    fn check_output_size(&self, out_size: usize) {
+        match *self {
+            Scoring::Weighted(ref weights) => {
+                assert_eq!(out_size, weights.len());
+                assert!(weights.iter().all(|&weight| weight > 0));
+            },
+            Scoring::NumericDistance => assert!(out_size < 31),
+            Scoring::Hamming | Scoring::ExactMatch => {},
+        }
+    }
+}
+
+/// Unsigned integer represented by a little endian bitvector of less than 31 bits.
+fn bitvector_to_integer(bitvector: &[bool]) -> i32 {
+    (0..bitvector.len()).filter(|&i| bitvector[i]).map(|i| 1 << i).sum()
+}
+
+impl Checkpoint for Scoring {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        match *self {
+            Scoring::Hamming => writer.write("Hamming"),
+            Scoring::ExactMatch => writer.write("ExactMatch"),
+            Scoring::Weighted(ref weights) => {
+                writer.write("Weighted");
+                writer.write(weights.len());
+                for &weight in weights.iter() {
+                    writer.write(weight);
+                }
+            },
+            Scoring::NumericDistance => writer.write("NumericDistance"),
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Scoring> {
+        let name : String = reader.read()?;
+        match name.as_str() {
+            "Hamming" => Ok(Scoring::Hamming),
+            "ExactMatch" => Ok(Scoring::ExactMatch),
+            "Weighted" => {
+                let num_weights : usize = reader.read()?;
+                let mut weights = Vec::with_capacity(num_weights);
+                for _ in 0..num_weights {
+                    weights.push(reader.read()?);
+                }
+                Ok(Scoring::Weighted(weights))
+            },
+            "NumericDistance" => Ok(Scoring::NumericDistance),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown scoring in checkpoint: {}", name))),
+        }
+    }
+}
+
 /// Task to imitate another individual, even if it's a different species/type than the population that evolves to imitate it
 /// The inidividual must be stateless, that is, not having an internal state that can affect calculate_output.
 /// Note that in neural networks having recursion implies having an internal state.
+/// Stateful individuals can imitate sequences instead, see sequence::SequenceTask.
+///
+/// By default each output bit equal to the imitated one scores a point, other scorings can be chosen with new_scored.
+/// By default all the possible inputs are tested, which is infeasible for more than about 20 input bits.
+/// Bigger individuals can be rated on a random sample of inputs instead, see Sampling.
+/// Sampled inputs are drawn with replacement, so the same input may be tested more than once.
 ///
 /// # Examples
 ///
@@ -155,22 +973,110 @@ fn calculate_fitness_result(result: &Vec<bool>, v_tested: &Vec<bool>) -> i32 {
 /// use tetani::digital::BinOp;
 /// use tetani::digital::BinaryIndividual;
 /// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Sampling;
 /// let bi_and_2 = BinaryIndividual::new(BinOp::AND, 2);
 /// let bi_xor_2 = BinaryIndividual::new(BinOp::XOR, 2);
 /// let imitate_bi_and_2 = ImitationTask::new(bi_and_2);
 /// let imitate_bi_xor_2 = ImitationTask::new(bi_xor_2);
+///
+/// let bi_xor_32 = BinaryIndividual::new(BinOp::XOR, 32);
+/// let mut imitate_bi_xor_32 = ImitationTask::new(bi_xor_32);
+/// imitate_bi_xor_32.set_sampling(Sampling::PerGeneration { num_samples: 256, seed: 0 });
 /// ```
 #[derive(Debug)]
 pub struct ImitationTask<I: Individual> {
     indi: I,
+    scoring: Scoring,
+    sampling: Sampling,
+    /// Inputs currently tested, empty when sampling is exhaustive
+    samples: Vec< Vec<bool> >,
+    /// Whether individuals perfect on the sampled inputs are verified on all the inputs
+    verification: bool,
 }
 
 impl<I> ImitationTask<I>
     where I: Individual + 'static + Clone {
 
+    /// Scores one point per output bit right, see Scoring::Hamming.
     pub fn new(indi: I) -> ImitationTask<I> {
+        ImitationTask::new_scored(indi, Scoring::Hamming)
+    }
+
+    pub fn new_scored(indi: I, scoring: Scoring) -> ImitationTask<I> {
+        scoring.check_output_size(indi.output_size());
         ImitationTask {
             indi,
+            scoring,
+            sampling: Sampling::Exhaustive,
+            samples: vec![],
+            verification: false,
+        }
+    }
+
+    pub fn set_sampling(&mut self, sampling: Sampling) {
+        self.samples = match sampling {
+            Sampling::Exhaustive => vec![],
+            Sampling::Fixed { num_samples, seed } | Sampling::PerGeneration { num_samples, seed } => {
+                assert!(num_samples > 0);
+                let mut rng = Pcg32::seed_from_u64(seed);
+                self.draw_samples(num_samples, &mut rng)
+            },
+        };
+        self.sampling = sampling;
+    }
+
+    pub fn scoring(&self) -> &Scoring {
+        &self.scoring
+    }
+
+    pub fn sampling(&self) -> &Sampling {
+        &self.sampling
+    }
+
+    /// Inputs currently tested, empty when sampling is exhaustive.
+    pub fn samples(&self) -> &[Vec<bool>] {
+        &self.samples
+    }
+
+    /// With verification, an individual getting the max fitness on the sampled inputs is also tested on all the inputs,
+    /// and gets one less than the max fitness unless it imitates all of them.
+    /// Only individuals that really imitate the task reach the max fitness, at the cost of an exhaustive test for each candidate.
+    pub fn set_verification(&mut self, verification: bool) {
+        self.verification = verification;
+    }
+
+    fn draw_samples(&self, num_samples: usize, rng: &mut dyn RngCore) -> Vec< Vec<bool> > {
+        (0..num_samples).map(|_| get_rand_bitvector(self.indi.input_size(), rng)).collect()
+    }
+
+    fn fitness_for_input(&self, other: &dyn Individual, input: &[bool]) -> i32 {
+        let output_self = self.indi.calculate_output(input);
+        let output_other = other.calculate_output(input);
+        self.scoring.score(&output_self, &output_other)
+    }
+
+    fn max_fitness_for_input(&self) -> i32 {
+        self.scoring.max_score(self.indi.output_size())
+    }
+
+    fn exhaustive_max_fitness(&self) -> i32 {
+        self.max_fitness_for_input().checked_mul(2i32.pow(self.indi.input_size() as u32)).expect("max fitness overflow")
+    }
+
+    /// Score of the individual for each of the possible inputs, see all_bitvectors.
+    fn exhaustive_scores(&self, other: &dyn Individual) -> Vec<i32> {
+        all_bitvectors(self.indi.input_size()).map(|input| self.fitness_for_input(other, &input)).collect()
+    }
+
+    /// Score of the individual for each tested input: all the possible inputs when sampling is exhaustive, the samples otherwise.
+    /// The fitness is their sum, and the case errors how far each of them is from the max score.
+    fn input_scores(&self, other: &dyn Individual) -> Vec<i32> {
+        assert_eq!(self.indi.output_size(), other.output_size());
+        assert_eq!(self.indi.input_size(), other.input_size());
+        if self.sampling == Sampling::Exhaustive {
+            self.exhaustive_scores(other)
+        } else {
+            self.samples.iter().map(|input| self.fitness_for_input(other, input)).collect()
         }
     }
 }
@@ -181,43 +1087,114 @@ impl<I> Clone for ImitationTask<I>
     fn clone(&self) -> ImitationTask<I> {
         ImitationTask {
             indi: self.indi.clone(),
+            scoring: self.scoring.clone(),
+            sampling: self.sampling.clone(),
+            samples: self.samples.clone(),
+            verification: self.verification,
+        }
+    }
+}
+
+impl Checkpoint for Sampling {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        match *self {
+            Sampling::Exhaustive => writer.write("Exhaustive"),
+            Sampling::Fixed { num_samples, seed } => {
+                writer.write("Fixed");
+                writer.write(num_samples);
+                writer.write(seed);
+            },
+            Sampling::PerGeneration { num_samples, seed } => {
+                writer.write("PerGeneration");
+                writer.write(num_samples);
+                writer.write(seed);
+            },
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Sampling> {
+        let name : String = reader.read()?;
+        match name.as_str() {
+            "Exhaustive" => Ok(Sampling::Exhaustive),
+            "Fixed" => Ok(Sampling::Fixed {
+                num_samples: reader.read()?,
+                seed: reader.read()?,
+            }),
+            "PerGeneration" => Ok(Sampling::PerGeneration {
+                num_samples: reader.read()?,
+                seed: reader.read()?,
+            }),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown sampling in checkpoint: {}", name))),
+        }
+    }
+}
+
+/// The current samples are saved too, so a resumed evolution keeps testing the same inputs.
+impl<I> Checkpoint for ImitationTask<I>
+    where I: Individual + 'static + Clone + Checkpoint {
+
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("ImitationTask");
+        self.indi.save(writer);
+        self.scoring.save(writer);
+        self.sampling.save(writer);
+        writer.write(self.verification);
+        writer.write(self.samples.len());
+        for sample in self.samples.iter() {
+            writer.write_bools(sample);
         }
     }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result< ImitationTask<I> > {
+        reader.expect("ImitationTask")?;
+        let indi = I::load(reader)?;
+        let mut task = ImitationTask::new_scored(indi, Scoring::load(reader)?);
+        task.sampling = Sampling::load(reader)?;
+        task.verification = reader.read()?;
+        let num_samples : usize = reader.read()?;
+        for _ in 0..num_samples {
+            task.samples.push(reader.read_bools()?);
+        }
+        Ok(task)
+    }
 }
 
 impl<I> Task for ImitationTask<I>
     where I: Individual + 'static + Clone {
 
-    fn calculate_fitness(&self, other: &Individual) -> i32 {
-        assert_eq!(self.indi.output_size(), other.output_size());
-        let in_size = self.indi.input_size();
-        assert_eq!(in_size, other.input_size());
-
-        let mut fitness = 0;
-        let mut input = get_null_bitvector(in_size);
-        let input_space_cardinality = 2usize.pow(in_size as u32);
-
-        for j in 0..input_space_cardinality {
-            let output_self = self.indi.calculate_output(&input);
-            let output_other = other.calculate_output(&input);
-            fitness += calculate_fitness_result(&output_self, &output_other);
-            // println!("----------------------------------------------------------");
-            // print!("input:  "); print_bitvector(&input);
-            // print!("A:      "); print_bitvector(&input[0..self.indi.input_size() / 2]);
-            // print!("B:      "); print_bitvector(&input[self.indi.input_size() / 2..self.indi.input_size()]);
-            // print!("INDI:   "); print_bitvector(&output_self);
-            // print!("OTHER:   "); print_bitvector(&output_other);
-            // print!("FITNESS: {}", fitness);
-
-            if j < input_space_cardinality - 1 {
-                increment_bitvector(&mut input);
-            }
+    fn calculate_fitness(&self, other: &dyn Individual) -> i32 {
+        let fitness : i32 = self.input_scores(other).iter().sum();
+        let max_fitness = self.max_fitness();
+        if self.sampling != Sampling::Exhaustive && self.verification && fitness == max_fitness
+            && self.exhaustive_scores(other).iter().sum::<i32>() < self.exhaustive_max_fitness() {
+            return max_fitness - 1;
         }
-
         fitness
     }
 
+    /// One case per tested input, whose error is how far its score is from the max score. Verification doesn't apply to cases.
+    fn calculate_case_errors(&self, other: &dyn Individual) -> Vec<i32> {
+        let max_score = self.max_fitness_for_input();
+        self.input_scores(other).iter().map(|score| max_score - score).collect()
+    }
+
+    /// The max score of each tested input.
     fn max_fitness(&self) -> i32 {
-        self.indi.output_size() as i32 * 2i32.pow(self.indi.input_size() as u32)
+        match self.sampling {
+            Sampling::Exhaustive => self.exhaustive_max_fitness(),
+            Sampling::Fixed { num_samples, .. } | Sampling::PerGeneration { num_samples, .. } => {
+                self.max_fitness_for_input().checked_mul(num_samples as i32).expect("max fitness overflow")
+            },
+        }
+    }
+
+    /// Draws new samples when sampling per generation.
+    fn prepare_generation(&mut self, rng: &mut dyn RngCore) -> bool {
+        if let Sampling::PerGeneration { num_samples, .. } = self.sampling {
+            self.samples = self.draw_samples(num_samples, rng);
+            true
+        } else {
+            false
+        }
     }
 }
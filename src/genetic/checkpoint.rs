@@ -0,0 +1,387 @@
+//! Saving a population to a file and restoring it later, so that long runs can be interrupted and resumed.
+//! Checkpoints are plain text: whitespace separated tokens, with labels to detect corrupted or mismatched files.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::path::Path;
+use std::str::{
+    FromStr,
+    SplitWhitespace,
+};
+
+use rand_pcg::Pcg32;
+
+use crate::genetic::{
+    Crossover,
+    Individual,
+    Population,
+    RatedIndividual,
+    Replacement,
+    Task,
+};
+use crate::genetic::hall_of_fame::HallOfFame;
+use crate::genetic::mutation::Mutation;
+
+/// Identifies population checkpoints and the version of their format.
+const POPULATION_HEADER: &str = "tetani-population-checkpoint 5";
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Builds the text of a checkpoint.
+#[derive(Default)]
+pub struct CheckpointWriter {
+    text: String,
+}
+
+impl CheckpointWriter {
+    pub fn new() -> CheckpointWriter {
+        CheckpointWriter {
+            text: String::new(),
+        }
+    }
+
+    /// Writes a single token, which must not contain whitespace.
+    pub fn write<D: Display>(&mut self, value: D) {
+        self.text.push_str(&value.to_string());
+        self.text.push(' ');
+    }
+
+    /// Writes a vector of bools as a single token of 0s and 1s.
+    pub fn write_bools(&mut self, bools: &[bool]) {
+        if bools.is_empty() {
+            self.text.push('-');
+        }
+        for &bit in bools.iter() {
+            self.text.push(if bit { '1' } else { '0' });
+        }
+        self.text.push(' ');
+    }
+
+    /// Line breaks are ignored when reading, they only make checkpoints easier to read.
+    pub fn new_line(&mut self) {
+        self.text.push('\n');
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+/// Reads the tokens of a checkpoint in the same order they were written.
+pub struct CheckpointReader<'a> {
+    tokens: SplitWhitespace<'a>,
+}
+
+impl<'a> CheckpointReader<'a> {
+    pub fn new(text: &'a str) -> CheckpointReader<'a> {
+        CheckpointReader {
+            tokens: text.split_whitespace(),
+        }
+    }
+
+    fn next_token(&mut self) -> io::Result<&'a str> {
+        self.tokens.next().ok_or_else(|| invalid_data("unexpected end of checkpoint".to_string()))
+    }
+
+    pub fn read<F: FromStr>(&mut self) -> io::Result<F> {
+        let token = self.next_token()?;
+        token.parse().map_err(|_| invalid_data(format!("invalid value in checkpoint: {}", token)))
+    }
+
+    pub fn read_bools(&mut self) -> io::Result< Vec<bool> > {
+        let token = self.next_token()?;
+        if token == "-" {
+            return Ok(vec![]);
+        }
+        token.chars().map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(invalid_data(format!("invalid bits in checkpoint: {}", token))),
+        }).collect()
+    }
+
+    /// Fails unless the next token is the given label.
+    pub fn expect(&mut self, label: &str) -> io::Result<()> {
+        let token = self.next_token()?;
+        if token == label {
+            Ok(())
+        } else {
+            Err(invalid_data(format!("expected {} in checkpoint, found {}", label, token)))
+        }
+    }
+}
+
+/// Individuals, tasks and settings that can be saved to a checkpoint and restored exactly.
+pub trait Checkpoint: Sized {
+    fn save(&self, writer: &mut CheckpointWriter);
+    fn load(reader: &mut CheckpointReader) -> io::Result<Self>;
+}
+
+impl Checkpoint for Crossover {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write(match self {
+            Crossover::OnePoint => "OnePoint",
+            Crossover::TwoPoint => "TwoPoint",
+            Crossover::Uniform => "Uniform",
+        });
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Crossover> {
+        match reader.next_token()? {
+            "OnePoint" => Ok(Crossover::OnePoint),
+            "TwoPoint" => Ok(Crossover::TwoPoint),
+            "Uniform" => Ok(Crossover::Uniform),
+            token => Err(invalid_data(format!("unknown crossover in checkpoint: {}", token))),
+        }
+    }
+}
+
+impl Checkpoint for Replacement {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        match *self {
+            Replacement::SteadyState => writer.write("SteadyState"),
+            Replacement::Generational { elites } => {
+                writer.write("Generational");
+                writer.write(elites);
+            },
+            Replacement::MuPlusLambda => writer.write("MuPlusLambda"),
+            Replacement::MuCommaLambda => writer.write("MuCommaLambda"),
+            Replacement::DeterministicCrowding => writer.write("DeterministicCrowding"),
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Replacement> {
+        match reader.next_token()? {
+            "SteadyState" => Ok(Replacement::SteadyState),
+            "Generational" => Ok(Replacement::Generational { elites: reader.read()? }),
+            "MuPlusLambda" => Ok(Replacement::MuPlusLambda),
+            "MuCommaLambda" => Ok(Replacement::MuCommaLambda),
+            "DeterministicCrowding" => Ok(Replacement::DeterministicCrowding),
+            token => Err(invalid_data(format!("unknown replacement in checkpoint: {}", token))),
+        }
+    }
+}
+
+impl<I, T> Population<I, T>
+    where I: Individual + 'static + Clone + Send + Sync + Hash + Checkpoint, T: Task + Sync + Checkpoint {
+
+    /// Writes the individuals, the task, the counters, the settings, the fitness cache and the hall of fame to a checkpoint.
+    /// The selection strategy, the distance, the diversity policy and the observers are not saved, they must be set again after loading.
+    /// During a mutation boost of the diversity policy, the mutation from before the boost is saved, see Population::unboosted_mutation.
+    /// The exact state of the random number generator is saved, so that a population loaded from the checkpoint
+    /// continues exactly like the saved one, and saving doesn't change how the saved one continues.
+    pub fn write_checkpoint(&self, writer: &mut CheckpointWriter) {
+        let rng_state = bincode::serialize(&self.rng).expect("the random number generator can always be serialized");
+
+        writer.write(POPULATION_HEADER);
+        writer.new_line();
+        writer.write("generation");
+        writer.write(self.generation);
+        writer.write("num_evaluations");
+        writer.write(self.num_evaluations);
+        writer.write("rng");
+        writer.write(rng_state.len());
+        for byte in rng_state {
+            writer.write(byte);
+        }
+        writer.new_line();
+        writer.write("capacity");
+        writer.write(self.capacity);
+        writer.write("num_threads");
+        writer.write(self.num_threads);
+        writer.write("num_selected");
+        writer.write(self.num_selected);
+        writer.write("num_offspring");
+        writer.write(self.num_offspring.unwrap_or(0));
+        writer.new_line();
+        writer.write("replacement");
+        self.replacement.save(writer);
+        writer.write("crossover_rate");
+        writer.write(self.crossover_rate);
+        writer.write("crossover");
+        self.crossover.save(writer);
+        writer.write("mutation");
+        self.unboosted_mutation().save(writer);
+        writer.new_line();
+        writer.write("task");
+        self.task.save(writer);
+        writer.new_line();
+        writer.write("pop");
+        writer.write(self.pop.len());
+        writer.new_line();
+        for rated in self.pop.iter() {
+            writer.write(rated.fitness);
+            rated.indi.save(writer);
+            writer.new_line();
+        }
+        writer.write("unrated_pop");
+        writer.write(self.unrated_pop.len());
+        writer.new_line();
+        for indi in self.unrated_pop.iter() {
+            indi.save(writer);
+            writer.new_line();
+        }
+        writer.write("cache_hits");
+        writer.write(self.cache_hits);
+        writer.write("cache_misses");
+        writer.write(self.cache_misses);
+        writer.new_line();
+        match self.fitness_cache {
+            Some(ref fitness_cache) => {
+                writer.write("fitness_cache");
+                writer.write(fitness_cache.len());
+                writer.new_line();
+                for (hash, fitness) in fitness_cache.iter() {
+                    writer.write(hash);
+                    writer.write(fitness);
+                    writer.new_line();
+                }
+            },
+            None => writer.write("no_fitness_cache"),
+        }
+        writer.new_line();
+        match self.hall_of_fame {
+            Some(ref hall_of_fame) => {
+                writer.write("hall_of_fame");
+                writer.write(self.hall_of_fame_elites);
+                hall_of_fame.save(writer);
+            },
+            None => writer.write("no_hall_of_fame"),
+        }
+        writer.new_line();
+    }
+
+    /// Restores a population written by write_checkpoint, with truncation selection, phenotypic distance and no observers.
+    pub fn read_checkpoint(reader: &mut CheckpointReader) -> io::Result< Population<I, T> > {
+        for label in POPULATION_HEADER.split_whitespace() {
+            reader.expect(label)?;
+        }
+        reader.expect("generation")?;
+        let generation = reader.read()?;
+        reader.expect("num_evaluations")?;
+        let num_evaluations = reader.read()?;
+        reader.expect("rng")?;
+        let rng_state_len : usize = reader.read()?;
+        let mut rng_state : Vec<u8> = Vec::with_capacity(rng_state_len.min(64));
+        for _ in 0..rng_state_len {
+            rng_state.push(reader.read()?);
+        }
+        let rng : Pcg32 = bincode::deserialize(&rng_state)
+            .map_err(|error| invalid_data(format!("invalid random number generator state in checkpoint: {}", error)))?;
+        reader.expect("capacity")?;
+        let capacity : usize = reader.read()?;
+        if capacity == 0 {
+            return Err(invalid_data("population capacity must be positive".to_string()));
+        }
+        reader.expect("num_threads")?;
+        let num_threads : usize = reader.read()?;
+        reader.expect("num_selected")?;
+        let num_selected : usize = reader.read()?;
+        if num_selected == 0 {
+            return Err(invalid_data("number of selected individuals must be positive".to_string()));
+        }
+        reader.expect("num_offspring")?;
+        let num_offspring : usize = reader.read()?;
+        reader.expect("replacement")?;
+        let replacement = Replacement::load(reader)?;
+        reader.expect("crossover_rate")?;
+        let crossover_rate : f64 = reader.read()?;
+        if !(0.0..=1.0).contains(&crossover_rate) {
+            return Err(invalid_data(format!("invalid crossover rate in checkpoint: {}", crossover_rate)));
+        }
+        reader.expect("crossover")?;
+        let crossover = Crossover::load(reader)?;
+        reader.expect("mutation")?;
+        let mutation = Mutation::load(reader)?;
+        reader.expect("task")?;
+        let task = T::load(reader)?;
+
+        let mut pop = Population::new(task, capacity);
+        pop.generation = generation;
+        pop.num_evaluations = num_evaluations;
+        pop.rng = rng;
+        pop.set_num_threads(num_threads.max(1));
+        pop.num_selected = num_selected;
+        pop.num_offspring = if num_offspring == 0 { None } else { Some(num_offspring) };
+        pop.replacement = replacement;
+        pop.crossover_rate = crossover_rate;
+        pop.crossover = crossover;
+        pop.mutation = mutation;
+
+        reader.expect("pop")?;
+        let pop_len : usize = reader.read()?;
+        for _ in 0..pop_len {
+            let fitness = reader.read()?;
+            let indi = I::load(reader)?;
+            // Already ordered, so pushing keeps the order among individuals with the same fitness
+            pop.pop.push(RatedIndividual{indi, fitness});
+        }
+        reader.expect("unrated_pop")?;
+        let unrated_len : usize = reader.read()?;
+        for _ in 0..unrated_len {
+            pop.unrated_pop.push(I::load(reader)?);
+        }
+        reader.expect("cache_hits")?;
+        pop.cache_hits = reader.read()?;
+        reader.expect("cache_misses")?;
+        pop.cache_misses = reader.read()?;
+        if reader.next_token()? == "fitness_cache" {
+            let cache_len : usize = reader.read()?;
+            let mut fitness_cache : HashMap<u64, i32> = HashMap::with_capacity(cache_len);
+            for _ in 0..cache_len {
+                let hash = reader.read()?;
+                let fitness = reader.read()?;
+                fitness_cache.insert(hash, fitness);
+            }
+            pop.fitness_cache = Some(fitness_cache);
+        }
+        if reader.next_token()? == "hall_of_fame" {
+            pop.hall_of_fame_elites = reader.read()?;
+            pop.hall_of_fame = Some(HallOfFame::load(reader)?);
+        }
+        Ok(pop)
+    }
+
+    /// Saves a checkpoint to a file, see write_checkpoint.
+    /// The checkpoint is written to a temporary file first, so an interrupted save doesn't corrupt a previous checkpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tetani::digital::BinOp;
+    /// use tetani::digital::BinaryIndividual;
+    /// use tetani::digital::TruthTable;
+    /// use tetani::genetic::ImitationTask;
+    /// use tetani::genetic::Population;
+    /// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+    /// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 10);
+    /// pop.add_unrated_individual(TruthTable::new_null(2, 1));
+    /// pop.rate_unrated_individuals();
+    /// pop.learn_task(10);
+    /// let path = std::env::temp_dir().join("tetani_doc_checkpoint.txt");
+    /// pop.save_checkpoint(&path).unwrap();
+    /// let mut resumed : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::load_checkpoint(&path).unwrap();
+    /// assert_eq!(pop.generation(), resumed.generation());
+    /// resumed.learn_task(100);
+    /// ```
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut writer = CheckpointWriter::new();
+        self.write_checkpoint(&mut writer);
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".tmp");
+        fs::write(&temporary_path, writer.into_string())?;
+        fs::rename(&temporary_path, path)
+    }
+
+    /// Loads a population saved with save_checkpoint.
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> io::Result< Population<I, T> > {
+        let text = fs::read_to_string(path)?;
+        Population::read_checkpoint(&mut CheckpointReader::new(&text))
+    }
+}
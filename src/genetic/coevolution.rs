@@ -0,0 +1,377 @@
+//! Competitive co-evolution: the fitness of an individual comes from games against other evolving individuals,
+//! either of its own population (self-play) or of a second population (hosts against parasites).
+//! Since the opponents change every generation, fitnesses are relative to the current opponents and don't have a fixed goal.
+
+use std::hash::Hash;
+
+use rand::{
+    RngCore,
+    SeedableRng,
+};
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg32;
+
+use crate::genetic::{
+    Individual,
+    Population,
+    Task,
+};
+
+/// A game between two individuals, for example each choosing a move with its output.
+pub trait Game {
+    /// Plays a game and returns the scores of the first and the second player, higher is better.
+    fn play(&self, first: &dyn Individual, second: &dyn Individual) -> (i32, i32);
+    /// Best score a player can get in a single game.
+    fn max_score(&self) -> i32;
+}
+
+/// Whether the rated individuals play first or second in their games.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Role {
+    First,
+    Second,
+}
+
+/// How the opponents of each generation are chosen from the opponent population.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Opponents {
+    /// Every individual of the opponent population.
+    RoundRobin,
+    /// The given number of different random individuals of the opponent population, drawn again every generation.
+    Random(usize),
+    /// The given number of different random champions of the opponent population, drawn again every generation.
+    /// The champions are the best individual of each of the last generations, kept by Coevolution or SelfPlay whatever their fitness,
+    /// so that individuals keep beating the strategies of the past. Before the first generation, the current best of the opponent population.
+    Champions(usize),
+    /// The given number of different random members of the hall of fame of the opponent population, drawn again every generation.
+    /// Unlike Champions, these are the fittest individuals ever rated by the opponent population, whatever their generation,
+    /// whose hall of fame must be enabled (see Population::enable_hall_of_fame).
+    Archive(usize),
+}
+
+/// Champions kept by default, see Coevolution::set_max_champions and SelfPlay::set_max_champions.
+pub const DEFAULT_MAX_CHAMPIONS: usize = 100;
+
+/// Adds the champion of the last generation, forgetting the oldest one beyond max_champions.
+fn push_champion<I>(champions: &mut Vec<I>, champion: I, max_champions: usize) {
+    champions.push(champion);
+    if champions.len() > max_champions {
+        champions.remove(0);
+    }
+}
+
+/// Chooses the opponents among the opponent population and the champions of its past generations.
+fn choose_opponents<I, T>(opponents: &Opponents, opponent_pop: &Population<I, T>, champions: &[I], rng: &mut dyn RngCore) -> Vec<I>
+    where I: Individual + Clone, T: Task {
+
+    let pop = &opponent_pop.pop;
+    match *opponents {
+        Opponents::RoundRobin => pop.iter().map(|rated| rated.indi.clone()).collect(),
+        Opponents::Random(num_opponents) => {
+            pop.choose_multiple(rng, num_opponents).map(|rated| rated.indi.clone()).collect()
+        },
+        Opponents::Champions(num_opponents) => {
+            if champions.is_empty() {
+                pop.iter().take(1).map(|rated| rated.indi.clone()).collect()
+            } else {
+                champions.choose_multiple(rng, num_opponents).cloned().collect()
+            }
+        },
+        Opponents::Archive(num_opponents) => {
+            let hall_of_fame = opponent_pop.hall_of_fame.as_ref().expect("the opponent population has no hall of fame");
+            hall_of_fame.sample(num_opponents, rng)
+        },
+    }
+}
+
+/// Task rating individuals by the sum of their scores in games against each of the opponents.
+/// The opponents are set before each generation, see Coevolution and SelfPlay.
+pub struct CoevolutionTask<O: Individual, G: Game> {
+    game: G,
+    role: Role,
+    opponents: Vec<O>,
+}
+
+impl<O, G> CoevolutionTask<O, G>
+    where O: Individual, G: Game {
+
+    /// A task without opponents, so every individual has fitness 0 until they are set.
+    pub fn new(game: G, role: Role) -> CoevolutionTask<O, G> {
+        CoevolutionTask {
+            game,
+            role,
+            opponents: vec![],
+        }
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn opponents(&self) -> &[O] {
+        &self.opponents
+    }
+
+    pub fn set_opponents(&mut self, opponents: Vec<O>) {
+        self.opponents = opponents;
+    }
+
+    fn score(&self, indi: &dyn Individual, opponent: &O) -> i32 {
+        match self.role {
+            Role::First => self.game.play(indi, opponent).0,
+            Role::Second => self.game.play(opponent, indi).1,
+        }
+    }
+}
+
+impl<O, G> Task for CoevolutionTask<O, G>
+    where O: Individual, G: Game {
+
+    fn calculate_fitness(&self, indi: &dyn Individual) -> i32 {
+        self.opponents.iter().map(|opponent| self.score(indi, opponent)).sum()
+    }
+
+    fn max_fitness(&self) -> i32 {
+        self.game.max_score() * self.opponents.len() as i32
+    }
+
+    /// One case per opponent.
+    fn calculate_case_errors(&self, indi: &dyn Individual) -> Vec<i32> {
+        self.opponents.iter().map(|opponent| self.game.max_score() - self.score(indi, opponent)).collect()
+    }
+}
+
+/// Two populations evolving against each other: hosts are rated by playing first against parasites, and parasites by playing
+/// second against hosts. Both may be of different types. Before each generation, the opponents of both populations are chosen
+/// and both populations are rated again against them.
+///
+/// Every individual of both populations must be rated (against no opponents, so with fitness 0) before the first generation.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::Individual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::coevolution::Coevolution;
+/// use tetani::genetic::coevolution::CoevolutionTask;
+/// use tetani::genetic::coevolution::Game;
+/// use tetani::genetic::coevolution::Opponents;
+/// use tetani::genetic::coevolution::Role;
+///
+/// /// Matching pennies: the first player wins if both outputs are equal, the second one if they differ.
+/// #[derive(Clone)]
+/// struct MatchingPennies;
+///
+/// impl Game for MatchingPennies {
+///     fn play(&self, first: &dyn Individual, second: &dyn Individual) -> (i32, i32) {
+///         if first.calculate_output(&[false]) == second.calculate_output(&[false]) { (1, 0) } else { (0, 1) }
+///     }
+///
+///     fn max_score(&self) -> i32 {
+///         1
+///     }
+/// }
+///
+/// let mut hosts : Population<TruthTable, CoevolutionTask<TruthTable, MatchingPennies>> =
+///     Population::new(CoevolutionTask::new(MatchingPennies, Role::First), 10);
+/// let mut parasites : Population<TruthTable, CoevolutionTask<TruthTable, MatchingPennies>> =
+///     Population::new(CoevolutionTask::new(MatchingPennies, Role::Second), 10);
+/// hosts.add_unrated_individual(TruthTable::new_null(1, 1));
+/// hosts.rate_unrated_individuals();
+/// parasites.add_unrated_individual(TruthTable::new_null(1, 1));
+/// parasites.rate_unrated_individuals();
+/// let mut coevolution = Coevolution::new(hosts, parasites);
+/// coevolution.set_opponents(Opponents::Random(5));
+/// coevolution.evolve(100);
+/// ```
+pub struct Coevolution<A, B, G>
+    where A: Individual, B: Individual, G: Game {
+
+    hosts: Population<A, CoevolutionTask<B, G>>,
+    parasites: Population<B, CoevolutionTask<A, G>>,
+    opponents: Opponents,
+    /// Best host of each of the last generations, oldest first
+    host_champions: Vec<A>,
+    /// Best parasite of each of the last generations, oldest first
+    parasite_champions: Vec<B>,
+    /// Number of generations whose champions are kept
+    max_champions: usize,
+    generation: usize,
+    rng: Pcg32,
+}
+
+impl<A, B, G> Coevolution<A, B, G>
+    where A: Individual + 'static + Clone + Send + Sync + Hash,
+          B: Individual + 'static + Clone + Send + Sync + Hash,
+          G: Game + Sync {
+
+    /// Opponents are chosen round robin by default.
+    pub fn new(hosts: Population<A, CoevolutionTask<B, G>>, parasites: Population<B, CoevolutionTask<A, G>>) -> Coevolution<A, B, G> {
+        Coevolution {
+            hosts,
+            parasites,
+            opponents: Opponents::RoundRobin,
+            host_champions: vec![],
+            parasite_champions: vec![],
+            max_champions: DEFAULT_MAX_CHAMPIONS,
+            generation: 0,
+            rng: Pcg32::from_rng(rand::thread_rng()).unwrap(),
+        }
+    }
+
+    pub fn set_opponents(&mut self, opponents: Opponents) {
+        self.opponents = opponents;
+    }
+
+    /// Only the champions of the last max_champions generations are kept, DEFAULT_MAX_CHAMPIONS by default.
+    pub fn set_max_champions(&mut self, max_champions: usize) {
+        assert!(max_champions > 0);
+        self.max_champions = max_champions;
+        let excess = self.host_champions.len().saturating_sub(max_champions);
+        self.host_champions.drain(..excess);
+        self.parasite_champions.drain(..excess);
+    }
+
+    /// Seeds the co-evolution, the hosts (with seed + 1) and the parasites (with seed + 2).
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Pcg32::seed_from_u64(seed);
+        self.hosts.set_seed(seed.wrapping_add(1));
+        self.parasites.set_seed(seed.wrapping_add(2));
+    }
+
+    pub fn hosts(&self) -> &Population<A, CoevolutionTask<B, G>> {
+        &self.hosts
+    }
+
+    pub fn hosts_mut(&mut self) -> &mut Population<A, CoevolutionTask<B, G>> {
+        &mut self.hosts
+    }
+
+    pub fn parasites(&self) -> &Population<B, CoevolutionTask<A, G>> {
+        &self.parasites
+    }
+
+    pub fn parasites_mut(&mut self) -> &mut Population<B, CoevolutionTask<A, G>> {
+        &mut self.parasites
+    }
+
+    /// Best host of each of the last generations, oldest first, see set_max_champions.
+    pub fn host_champions(&self) -> &[A] {
+        &self.host_champions
+    }
+
+    /// Best parasite of each of the last generations, oldest first, see set_max_champions.
+    pub fn parasite_champions(&self) -> &[B] {
+        &self.parasite_champions
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Both populations choose their opponents before any of them evolves.
+    pub fn next_generation(&mut self) {
+        let host_opponents = choose_opponents(&self.opponents, &self.parasites, &self.parasite_champions, &mut self.rng);
+        let parasite_opponents = choose_opponents(&self.opponents, &self.hosts, &self.host_champions, &mut self.rng);
+        self.hosts.task.set_opponents(host_opponents);
+        self.parasites.task.set_opponents(parasite_opponents);
+        self.hosts.rerate_population();
+        self.parasites.rerate_population();
+
+        self.hosts.next_generation();
+        self.parasites.next_generation();
+        push_champion(&mut self.host_champions, self.hosts.best().clone(), self.max_champions);
+        push_champion(&mut self.parasite_champions, self.parasites.best().clone(), self.max_champions);
+        self.generation += 1;
+    }
+
+    pub fn evolve(&mut self, num_generations: usize) {
+        for _ in 0..num_generations {
+            self.next_generation();
+        }
+    }
+}
+
+/// A single population whose individuals are rated by playing first against individuals of the same population.
+/// With round robin, individuals also play against themselves.
+///
+/// Every individual must be rated (against no opponents, so with fitness 0) before the first generation.
+pub struct SelfPlay<I, G>
+    where I: Individual, G: Game {
+
+    pop: Population<I, CoevolutionTask<I, G>>,
+    opponents: Opponents,
+    /// Best individual of each of the last generations, oldest first
+    champions: Vec<I>,
+    /// Number of generations whose champions are kept
+    max_champions: usize,
+    generation: usize,
+    rng: Pcg32,
+}
+
+impl<I, G> SelfPlay<I, G>
+    where I: Individual + 'static + Clone + Send + Sync + Hash, G: Game + Sync {
+
+    /// Opponents are chosen round robin by default.
+    pub fn new(pop: Population<I, CoevolutionTask<I, G>>) -> SelfPlay<I, G> {
+        SelfPlay {
+            pop,
+            opponents: Opponents::RoundRobin,
+            champions: vec![],
+            max_champions: DEFAULT_MAX_CHAMPIONS,
+            generation: 0,
+            rng: Pcg32::from_rng(rand::thread_rng()).unwrap(),
+        }
+    }
+
+    pub fn set_opponents(&mut self, opponents: Opponents) {
+        self.opponents = opponents;
+    }
+
+    /// Only the champions of the last max_champions generations are kept, DEFAULT_MAX_CHAMPIONS by default.
+    pub fn set_max_champions(&mut self, max_champions: usize) {
+        assert!(max_champions > 0);
+        self.max_champions = max_champions;
+        let excess = self.champions.len().saturating_sub(max_champions);
+        self.champions.drain(..excess);
+    }
+
+    /// Seeds the self-play and its population (with seed + 1).
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Pcg32::seed_from_u64(seed);
+        self.pop.set_seed(seed.wrapping_add(1));
+    }
+
+    pub fn population(&self) -> &Population<I, CoevolutionTask<I, G>> {
+        &self.pop
+    }
+
+    pub fn population_mut(&mut self) -> &mut Population<I, CoevolutionTask<I, G>> {
+        &mut self.pop
+    }
+
+    /// Best individual of each of the last generations, oldest first, see set_max_champions.
+    pub fn champions(&self) -> &[I] {
+        &self.champions
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn next_generation(&mut self) {
+        let opponents = choose_opponents(&self.opponents, &self.pop, &self.champions, &mut self.rng);
+        self.pop.task.set_opponents(opponents);
+        self.pop.rerate_population();
+        self.pop.next_generation();
+        push_champion(&mut self.champions, self.pop.best().clone(), self.max_champions);
+        self.generation += 1;
+    }
+
+    pub fn evolve(&mut self, num_generations: usize) {
+        for _ in 0..num_generations {
+            self.next_generation();
+        }
+    }
+}
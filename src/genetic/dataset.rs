@@ -0,0 +1,365 @@
+//! Learning from recorded examples instead of imitating another individual.
+//! A dataset is split into training examples, which rate the individuals, and validation and test examples,
+//! which measure how well the learnt individuals generalize to examples they have never been rated on.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg32;
+
+use crate::digital::all_bitvectors;
+use crate::genetic::{
+    calculate_fitness_result,
+    Individual,
+    Task,
+};
+use crate::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+
+fn invalid_data(line_number: usize, message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_number, message))
+}
+
+fn parse_bit(text: &str) -> Option<bool> {
+    match text {
+        "0" | "false" => Some(false),
+        "1" | "true" => Some(true),
+        _ => None,
+    }
+}
+
+fn parse_bits(text: &str) -> Option< Vec<bool> > {
+    text.chars().map(|c| match c {
+        '0' => Some(false),
+        '1' => Some(true),
+        _ => None,
+    }).collect()
+}
+
+/// An input together with the output expected for it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Example {
+    pub input: Vec<bool>,
+    pub output: Vec<bool>,
+}
+
+/// Examples with the same input and output sizes.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::genetic::dataset::Dataset;
+/// let text = "# a OR b\n00 0\n01 1\n10 1\n11 1\n";
+/// let dataset = Dataset::parse_text(text).unwrap();
+/// assert_eq!(4, dataset.len());
+/// assert_eq!(2, dataset.input_size());
+/// assert_eq!(1, dataset.output_size());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Dataset {
+    in_size: usize,
+    out_size: usize,
+    examples: Vec<Example>,
+}
+
+impl Dataset {
+    /// An empty dataset for the given sizes.
+    pub fn new(in_size: usize, out_size: usize) -> Dataset {
+        Dataset {
+            in_size,
+            out_size,
+            examples: vec![],
+        }
+    }
+
+    /// The outputs of the individual for all its possible inputs.
+    pub fn from_individual(indi: &dyn Individual) -> Dataset {
+        let in_size = indi.input_size();
+        let mut dataset = Dataset::new(in_size, indi.output_size());
+        dataset.examples = all_bitvectors(in_size).map(|input| {
+            let output = indi.calculate_output(&input);
+            Example{input, output}
+        }).collect();
+        dataset
+    }
+
+    /// Parses one example per line: the input bits and the output bits, separated by whitespace, as in "0110 1".
+    /// Blank lines and lines starting with # are ignored.
+    pub fn parse_text(text: &str) -> io::Result<Dataset> {
+        let mut examples = vec![];
+        for (k, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields : Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 2 {
+                return Err(invalid_data(k + 1, format!("expected input and output bits, found {}", line)));
+            }
+            match (parse_bits(fields[0]), parse_bits(fields[1])) {
+                (Some(input), Some(output)) => examples.push((k + 1, Example{input, output})),
+                _ => return Err(invalid_data(k + 1, format!("invalid bits: {}", line))),
+            }
+        }
+        Dataset::from_numbered_examples(examples)
+    }
+
+    /// Parses comma separated values with one bit per column, 0/1 or false/true: the first in_size columns are the input
+    /// and the rest are the output. A first line that isn't made of bits is taken as a header and skipped.
+    pub fn parse_csv(text: &str, in_size: usize) -> io::Result<Dataset> {
+        let mut examples = vec![];
+        for (k, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let bits : Option< Vec<bool> > = line.split(',').map(|cell| parse_bit(cell.trim())).collect();
+            match bits {
+                Some(bits) => {
+                    if bits.len() <= in_size {
+                        return Err(invalid_data(k + 1, format!("expected more than {} columns, found {}", in_size, bits.len())));
+                    }
+                    examples.push((k + 1, Example {
+                        input: bits[..in_size].to_vec(),
+                        output: bits[in_size..].to_vec(),
+                    }));
+                },
+                None if k == 0 => continue,
+                None => return Err(invalid_data(k + 1, format!("invalid bits: {}", line))),
+            }
+        }
+        Dataset::from_numbered_examples(examples)
+    }
+
+    /// Loads a file in the format of parse_text.
+    pub fn load_text<P: AsRef<Path>>(path: P) -> io::Result<Dataset> {
+        Dataset::parse_text(&fs::read_to_string(path)?)
+    }
+
+    /// Loads a file in the format of parse_csv.
+    pub fn load_csv<P: AsRef<Path>>(path: P, in_size: usize) -> io::Result<Dataset> {
+        Dataset::parse_csv(&fs::read_to_string(path)?, in_size)
+    }
+
+    /// The sizes are taken from the first example, and every other example must have the same sizes.
+    fn from_numbered_examples(examples: Vec<(usize, Example)>) -> io::Result<Dataset> {
+        let (in_size, out_size) = match examples.first() {
+            Some((_, example)) => (example.input.len(), example.output.len()),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "no examples in dataset")),
+        };
+        let mut dataset = Dataset::new(in_size, out_size);
+        for (line_number, example) in examples {
+            if example.input.len() != in_size || example.output.len() != out_size {
+                return Err(invalid_data(line_number, format!("expected {} input and {} output bits, found {} and {}",
+                                                             in_size, out_size, example.input.len(), example.output.len())));
+            }
+            dataset.examples.push(example);
+        }
+        Ok(dataset)
+    }
+
+    pub fn add_example(&mut self, example: Example) {
+        assert_eq!(self.in_size, example.input.len());
+        assert_eq!(self.out_size, example.output.len());
+        self.examples.push(example);
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.in_size
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.out_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.examples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.examples.is_empty()
+    }
+
+    pub fn examples(&self) -> &[Example] {
+        &self.examples
+    }
+
+    /// Shuffles the examples with the seed and splits them into training, validation and test datasets.
+    /// The ratios of training and validation examples are rounded, and the remaining examples are for testing.
+    pub fn split(&self, train_ratio: f64, validation_ratio: f64, seed: u64) -> (Dataset, Dataset, Dataset) {
+        assert!(train_ratio >= 0.0 && validation_ratio >= 0.0 && train_ratio + validation_ratio <= 1.0);
+        let mut examples = self.examples.clone();
+        examples.shuffle(&mut Pcg32::seed_from_u64(seed));
+        let num_train = ((examples.len() as f64 * train_ratio).round() as usize).min(examples.len());
+        let num_validation = ((examples.len() as f64 * validation_ratio).round() as usize).min(examples.len() - num_train);
+
+        let mut test = Dataset::new(self.in_size, self.out_size);
+        test.examples = examples.split_off(num_train + num_validation);
+        let mut validation = Dataset::new(self.in_size, self.out_size);
+        validation.examples = examples.split_off(num_train);
+        let mut train = Dataset::new(self.in_size, self.out_size);
+        train.examples = examples;
+        (train, validation, test)
+    }
+
+    /// Number of output bits of all the examples that the individual gets right.
+    pub fn fitness(&self, indi: &dyn Individual) -> i32 {
+        assert_eq!(self.in_size, indi.input_size());
+        assert_eq!(self.out_size, indi.output_size());
+        self.examples.iter()
+            .map(|example| calculate_fitness_result(&example.output, &indi.calculate_output(&example.input)))
+            .sum()
+    }
+
+    /// Number of output bits that the individual gets wrong for each example.
+    pub fn case_errors(&self, indi: &dyn Individual) -> Vec<i32> {
+        assert_eq!(self.in_size, indi.input_size());
+        assert_eq!(self.out_size, indi.output_size());
+        self.examples.iter()
+            .map(|example| self.out_size as i32 - calculate_fitness_result(&example.output, &indi.calculate_output(&example.input)))
+            .collect()
+    }
+
+    /// Number of output bits of all the examples.
+    pub fn max_fitness(&self) -> i32 {
+        (self.out_size * self.examples.len()) as i32
+    }
+}
+
+impl Checkpoint for Dataset {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("Dataset");
+        writer.write(self.in_size);
+        writer.write(self.out_size);
+        writer.write(self.examples.len());
+        for example in self.examples.iter() {
+            writer.new_line();
+            writer.write_bools(&example.input);
+            writer.write_bools(&example.output);
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Dataset> {
+        reader.expect("Dataset")?;
+        let mut dataset = Dataset::new(reader.read()?, reader.read()?);
+        let num_examples : usize = reader.read()?;
+        for _ in 0..num_examples {
+            let input = reader.read_bools()?;
+            let output = reader.read_bools()?;
+            if input.len() != dataset.in_size || output.len() != dataset.out_size {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "example of the wrong size in checkpoint"));
+            }
+            dataset.examples.push(Example{input, output});
+        }
+        Ok(dataset)
+    }
+}
+
+/// Task to learn the examples of a training dataset, one fitness point per output bit right.
+/// The validation and test datasets don't affect the fitness: they report how well individuals generalize,
+/// for example validating candidates during evolution and testing only the final one.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::dataset::Dataset;
+/// use tetani::genetic::dataset::DatasetTask;
+/// let dataset = Dataset::from_individual(&BinaryIndividual::new(BinOp::XOR, 4));
+/// let dataset_task = DatasetTask::from_split(&dataset, 0.5, 0.25, 0);
+/// let mut pop : Population<TruthTable, DatasetTask> = Population::new(dataset_task, 10);
+/// pop.add_unrated_individual(TruthTable::new_null(4, 2));
+/// pop.rate_unrated_individuals();
+/// pop.learn_task(100);
+/// println!("Training {}/{}, validation {}/{}",
+///          pop.best_fitness(), pop.task.train().max_fitness(),
+///          pop.task.validation_fitness(pop.best()), pop.task.validation().max_fitness());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatasetTask {
+    train: Dataset,
+    validation: Dataset,
+    test: Dataset,
+}
+
+impl DatasetTask {
+    pub fn new(train: Dataset, validation: Dataset, test: Dataset) -> DatasetTask {
+        for dataset in [&validation, &test].iter() {
+            assert_eq!(train.input_size(), dataset.input_size());
+            assert_eq!(train.output_size(), dataset.output_size());
+        }
+        DatasetTask {
+            train,
+            validation,
+            test,
+        }
+    }
+
+    /// Splits the dataset, see Dataset::split.
+    pub fn from_split(dataset: &Dataset, train_ratio: f64, validation_ratio: f64, seed: u64) -> DatasetTask {
+        let (train, validation, test) = dataset.split(train_ratio, validation_ratio, seed);
+        DatasetTask::new(train, validation, test)
+    }
+
+    pub fn train(&self) -> &Dataset {
+        &self.train
+    }
+
+    pub fn validation(&self) -> &Dataset {
+        &self.validation
+    }
+
+    pub fn test(&self) -> &Dataset {
+        &self.test
+    }
+
+    pub fn validation_fitness(&self, indi: &dyn Individual) -> i32 {
+        self.validation.fitness(indi)
+    }
+
+    pub fn test_fitness(&self, indi: &dyn Individual) -> i32 {
+        self.test.fitness(indi)
+    }
+}
+
+impl Checkpoint for DatasetTask {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("DatasetTask");
+        self.train.save(writer);
+        self.validation.save(writer);
+        self.test.save(writer);
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<DatasetTask> {
+        reader.expect("DatasetTask")?;
+        Ok(DatasetTask {
+            train: Dataset::load(reader)?,
+            validation: Dataset::load(reader)?,
+            test: Dataset::load(reader)?,
+        })
+    }
+}
+
+impl Task for DatasetTask {
+    fn calculate_fitness(&self, indi: &dyn Individual) -> i32 {
+        self.train.fitness(indi)
+    }
+
+    /// One case per training example, with one error per output bit wrong.
+    fn calculate_case_errors(&self, indi: &dyn Individual) -> Vec<i32> {
+        self.train.case_errors(indi)
+    }
+
+    fn max_fitness(&self) -> i32 {
+        self.train.max_fitness()
+    }
+}
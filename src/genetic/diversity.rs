@@ -0,0 +1,293 @@
+//! Measures of how diverse a population is, to detect premature convergence, and a policy reacting when diversity drops:
+//! restarting most of the population or boosting the mutation for a while.
+
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+use std::hash::Hash;
+
+use crate::genetic::{
+    behaviour,
+    Individual,
+    Population,
+    RatedIndividual,
+    Task,
+};
+use crate::genetic::genome::{
+    genome_distance,
+    Gene,
+    Genome,
+};
+use crate::genetic::mutation::{
+    mutate_genes_with_probability,
+    Mutation,
+};
+
+/// Mean genome distance (see genome_distance) over all the pairs of different individuals, 0 with less than two individuals.
+/// For individuals with bit genes, this is the mean pairwise Hamming distance.
+pub fn mean_pairwise_distance<I: Genome>(indis: &[&I]) -> f64 {
+    let num_indis = indis.len();
+    if num_indis < 2 {
+        return 0.0;
+    }
+    let mut total_distance = 0.0;
+    for i in 0..num_indis {
+        for j in i + 1..num_indis {
+            total_distance += genome_distance(indis[i], indis[j]);
+        }
+    }
+    total_distance / (num_indis * (num_indis - 1) / 2) as f64
+}
+
+/// Genes compared by value, real genes by their bits.
+fn gene_key(gene: &Gene) -> (u8, u64) {
+    match *gene {
+        Gene::Bit(bit) => (0, bit as u64),
+        Gene::Integer(integer) => (1, integer as u64),
+        Gene::Real(real) => (2, real.to_bits()),
+    }
+}
+
+/// Shannon entropy, in bits, of the values of each gene among the individuals, which must have genomes of the same shape.
+/// A gene has entropy 0 when all the individuals share its value, and 1 when half of them have each of two values.
+pub fn gene_entropies<I: Genome>(indis: &[&I]) -> Vec<f64> {
+    let genomes : Vec< Vec<Gene> > = indis.iter().map(|indi| indi.genes()).collect();
+    let num_genes = genomes.first().map(|genes| genes.len()).unwrap_or(0);
+    let num_indis = genomes.len() as f64;
+    (0..num_genes).map(|gene| {
+        let mut counts : HashMap<(u8, u64), usize> = HashMap::new();
+        for genes in genomes.iter() {
+            assert_eq!(num_genes, genes.len());
+            *counts.entry(gene_key(&genes[gene])).or_insert(0) += 1;
+        }
+        counts.values().map(|&count| {
+            let probability = count as f64 / num_indis;
+            -probability * probability.log2()
+        }).sum()
+    }).collect()
+}
+
+/// Mean of gene_entropies, 0 for individuals without genes.
+pub fn mean_gene_entropy<I: Genome>(indis: &[&I]) -> f64 {
+    let entropies = gene_entropies(indis);
+    if entropies.is_empty() {
+        return 0.0;
+    }
+    entropies.iter().sum::<f64>() / entropies.len() as f64
+}
+
+/// Number of different functions computed by the individuals, comparing their outputs for all the possible inputs.
+pub fn num_distinct_phenotypes<I: Individual>(indis: &[&I]) -> usize {
+    indis.iter().map(|indi| behaviour(*indi)).collect::< HashSet< Vec<bool> > >().len()
+}
+
+/// Genotypic and phenotypic diversity of a group of individuals.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diversity {
+    /// See mean_pairwise_distance
+    pub mean_pairwise_distance: f64,
+    /// See mean_gene_entropy
+    pub mean_gene_entropy: f64,
+    /// See num_distinct_phenotypes
+    pub num_distinct_phenotypes: usize,
+}
+
+impl Diversity {
+    pub fn new<I: Individual + Genome>(indis: &[&I]) -> Diversity {
+        Diversity {
+            mean_pairwise_distance: mean_pairwise_distance(indis),
+            mean_gene_entropy: mean_gene_entropy(indis),
+            num_distinct_phenotypes: num_distinct_phenotypes(indis),
+        }
+    }
+}
+
+/// The measure of diversity compared with the threshold of a DiversityPolicy.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DiversityMetric {
+    MeanPairwiseDistance,
+    MeanGeneEntropy,
+    DistinctPhenotypes,
+}
+
+/// What a DiversityPolicy does when the diversity drops below its threshold.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DiversityResponse {
+    /// Every individual but the best survivors is mutated on each gene with probability 1/2 and rated again.
+    /// For bit genes, this gives random genomes.
+    Restart { survivors: usize },
+    /// The mutation of the population is replaced by the given one for the given number of generations,
+    /// during which the diversity isn't checked.
+    MutationBoost { mutation: Mutation, generations: usize },
+}
+
+/// Checks the diversity of a population after every generation, and responds when it drops below a threshold.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::diversity::DiversityMetric;
+/// use tetani::genetic::diversity::DiversityPolicy;
+/// use tetani::genetic::diversity::DiversityResponse;
+/// use tetani::genetic::mutation::Mutation;
+/// let imitate_bi_xor_4 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 4));
+/// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_4, 16);
+/// pop.add_unrated_individual(TruthTable::new_null(4, 2));
+/// pop.rate_unrated_individuals();
+/// pop.set_num_offspring(8);
+/// let boost = DiversityResponse::MutationBoost { mutation: Mutation::PerGene(0.2), generations: 10 };
+/// pop.set_diversity_policy(DiversityPolicy::new(DiversityMetric::MeanGeneEntropy, 0.1, boost));
+/// pop.learn_task(100);
+/// println!("Diversity: {:?}", pop.diversity());
+/// ```
+pub struct DiversityPolicy<I: Individual> {
+    metric: DiversityMetric,
+    threshold: f64,
+    response: DiversityResponse,
+    /// Computes the metric for the individuals of the population, which needs their genomes
+    measure: fn(&[RatedIndividual<I>], &DiversityMetric) -> f64,
+    /// Mutation of the population before the current boost
+    unboosted_mutation: Option<Mutation>,
+    /// Generations left in the current boost
+    boost_generations_left: usize,
+    /// Number of times the diversity has dropped below the threshold
+    num_responses: usize,
+}
+
+fn measure_population<I: Individual + Genome>(pop: &[RatedIndividual<I>], metric: &DiversityMetric) -> f64 {
+    let indis : Vec<&I> = pop.iter().map(|rated| &rated.indi).collect();
+    match *metric {
+        DiversityMetric::MeanPairwiseDistance => mean_pairwise_distance(&indis),
+        DiversityMetric::MeanGeneEntropy => mean_gene_entropy(&indis),
+        DiversityMetric::DistinctPhenotypes => num_distinct_phenotypes(&indis) as f64,
+    }
+}
+
+impl<I> DiversityPolicy<I>
+    where I: Individual + Genome {
+
+    pub fn new(metric: DiversityMetric, threshold: f64, response: DiversityResponse) -> DiversityPolicy<I> {
+        if let DiversityResponse::MutationBoost { ref mutation, generations } = response {
+            assert!(mutation.is_valid(), "invalid mutation: {:?}", mutation);
+            assert!(generations > 0);
+        }
+        DiversityPolicy {
+            metric,
+            threshold,
+            response,
+            measure: measure_population::<I>,
+            unboosted_mutation: None,
+            boost_generations_left: 0,
+            num_responses: 0,
+        }
+    }
+}
+
+impl<I> DiversityPolicy<I>
+    where I: Individual {
+
+    pub fn metric(&self) -> &DiversityMetric {
+        &self.metric
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Number of restarts or boosts so far.
+    pub fn num_responses(&self) -> usize {
+        self.num_responses
+    }
+
+    pub fn is_boosting(&self) -> bool {
+        self.boost_generations_left > 0
+    }
+}
+
+impl<I, T> Population<I, T>
+    where I: Individual + 'static + Clone + Send + Sync + Hash + Genome, T: Task + Sync {
+
+    /// Diversity of the current population.
+    pub fn diversity(&self) -> Diversity {
+        let indis : Vec<&I> = self.pop.iter().map(|rated| &rated.indi).collect();
+        Diversity::new(&indis)
+    }
+
+    /// The policy checks the diversity after every generation. It is not saved in checkpoints.
+    /// Ends the mutation boost of the previous policy, if any, restoring the mutation from before it.
+    pub fn set_diversity_policy(&mut self, policy: DiversityPolicy<I>) {
+        self.end_mutation_boost();
+        self.diversity_policy = Some(policy);
+    }
+}
+
+impl<I, T> Population<I, T>
+    where I: Individual + 'static + Clone + Send + Sync + Hash, T: Task + Sync {
+
+    pub fn diversity_policy(&self) -> Option<&DiversityPolicy<I>> {
+        self.diversity_policy.as_ref()
+    }
+
+    /// The mutation of the population outside of mutation boosts: the one from before the current boost, if any.
+    pub fn unboosted_mutation(&self) -> &Mutation {
+        self.diversity_policy.as_ref()
+            .and_then(|policy| policy.unboosted_mutation.as_ref())
+            .unwrap_or(&self.mutation)
+    }
+
+    /// Ends the current mutation boost, if any, restoring the mutation from before it.
+    pub(crate) fn end_mutation_boost(&mut self) {
+        if let Some(ref mut policy) = self.diversity_policy {
+            if let Some(mutation) = policy.unboosted_mutation.take() {
+                self.mutation = mutation;
+            }
+            policy.boost_generations_left = 0;
+        }
+    }
+
+    /// Ends the mutation boost once it has lasted long enough, or responds if the diversity is below the threshold.
+    pub(crate) fn apply_diversity_policy(&mut self) {
+        let mut policy = match self.diversity_policy.take() {
+            Some(policy) => policy,
+            None => return,
+        };
+        if policy.boost_generations_left > 0 {
+            policy.boost_generations_left -= 1;
+            if policy.boost_generations_left == 0 {
+                self.mutation = policy.unboosted_mutation.take().unwrap();
+            }
+        } else if (policy.measure)(&self.pop, &policy.metric) < policy.threshold {
+            policy.num_responses += 1;
+            match policy.response {
+                DiversityResponse::Restart { survivors } => self.restart(survivors),
+                DiversityResponse::MutationBoost { ref mutation, generations } => {
+                    policy.unboosted_mutation = Some(std::mem::replace(&mut self.mutation, mutation.clone()));
+                    policy.boost_generations_left = generations;
+                },
+            }
+        }
+        self.diversity_policy = Some(policy);
+    }
+
+    /// Mutates every individual but the best survivors on each gene with probability 1/2, and rates them again.
+    fn restart(&mut self, survivors: usize) {
+        if self.pop.len() <= survivors {
+            return;
+        }
+        let mut restarted : Vec<I> = self.pop.split_off(survivors).into_iter().map(|rated| rated.indi).collect();
+        for indi in restarted.iter_mut() {
+            mutate_genes_with_probability(indi, 0.5, &mut self.rng);
+        }
+        let rated_indis = self.rate_individuals(restarted);
+        self.offer_to_hall_of_fame(&rated_indis);
+        for rated in rated_indis {
+            self.add_rated_individual(rated);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Uniform access to the genes of individuals, whatever their representation (bits of a table, weights of a network...),
+//! so that operators like crossover and distances can be written once for all of them.
+
+use rand::RngCore;
+
+use crate::genetic::{
+    crossover_genes,
+    Crossover,
+};
+
+/// A single gene of a genome.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Gene {
+    Bit(bool),
+    Integer(i32),
+    Real(f64),
+}
+
+impl Gene {
+    /// Distance between two genes of the same kind: 0 or 1 for bits, the absolute difference for numbers.
+    pub fn distance(&self, other: &Gene) -> f64 {
+        match (*self, *other) {
+            (Gene::Bit(bit), Gene::Bit(other_bit)) => if bit == other_bit { 0.0 } else { 1.0 },
+            (Gene::Integer(integer), Gene::Integer(other_integer)) => (integer as f64 - other_integer as f64).abs(),
+            (Gene::Real(real), Gene::Real(other_real)) => (real - other_real).abs(),
+            _ => panic!("genes of different kinds: {:?} and {:?}", self, other),
+        }
+    }
+
+    /// Panics if the gene isn't a bit.
+    pub fn bit(&self) -> bool {
+        match *self {
+            Gene::Bit(bit) => bit,
+            _ => panic!("expected a bit gene, found {:?}", self),
+        }
+    }
+
+    /// Panics if the gene isn't an integer.
+    pub fn integer(&self) -> i32 {
+        match *self {
+            Gene::Integer(integer) => integer,
+            _ => panic!("expected an integer gene, found {:?}", self),
+        }
+    }
+
+    /// Panics if the gene isn't a real number.
+    pub fn real(&self) -> f64 {
+        match *self {
+            Gene::Real(real) => real,
+            _ => panic!("expected a real gene, found {:?}", self),
+        }
+    }
+}
+
+/// Individuals whose genome can be read and written as a sequence of genes.
+/// The first Individual::num_genes genes are numbered like Individual::mutate_gene numbers them,
+/// and are followed by any other genes, like strategy parameters. Individuals that don't evolve have no genes.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::genome::Gene;
+/// use tetani::genetic::genome::Genome;
+/// let mut tt = TruthTable::new_null(1, 1);
+/// tt.set_genes(&[Gene::Bit(true), Gene::Bit(false)]);
+/// // Not
+/// assert_eq!(vec![Gene::Bit(true), Gene::Bit(false)], tt.genes());
+/// ```
+pub trait Genome {
+    fn genes(&self) -> Vec<Gene>;
+    /// Replaces the genes by as many genes as returned by genes, each of the same kind.
+    fn set_genes(&mut self, genes: &[Gene]);
+}
+
+/// A child whose genes are recombined from the genes of both parents, which must have the same shape.
+pub fn genome_crossover<I: Genome + Clone>(indi: &I, other: &I, crossover: &Crossover, rng: &mut dyn RngCore) -> I {
+    let genes = crossover_genes(&indi.genes(), &other.genes(), crossover, rng);
+    let mut child = indi.clone();
+    child.set_genes(&genes);
+    child
+}
+
+/// Sum of the distances between corresponding genes, see Gene::distance. For bit genes, this is the Hamming distance.
+pub fn genome_distance<I: Genome + ?Sized>(indi: &I, other: &I) -> f64 {
+    let genes = indi.genes();
+    let other_genes = other.genes();
+    assert_eq!(genes.len(), other_genes.len());
+    genes.iter().zip(other_genes.iter()).map(|(gene, other_gene)| gene.distance(other_gene)).sum()
+}
@@ -0,0 +1,191 @@
+//! An archive of the best individuals ever rated by a population, which survives even when the population loses them,
+//! for example with generational replacement or when a bounded population is full of individuals equally fit.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{
+    Hash,
+    Hasher,
+};
+use std::io;
+
+use rand::RngCore;
+use rand::seq::SliceRandom;
+
+use crate::genetic::{
+    behaviour,
+    Individual,
+    RatedIndividual,
+};
+use crate::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+
+/// The capacity best individuals seen so far, with at most one individual per phenotype (its behaviour, see genetic::behaviour).
+/// Among individuals with the same phenotype, the fittest is kept, and the first one seen on a tie.
+/// Computing phenotypes evaluates individuals on every input, so individuals must have few inputs.
+///
+/// Fitnesses are the ones individuals had when offered. If the task changes, Population::rerate_population rates the members again.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::Replacement;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 10);
+/// pop.set_replacement(Replacement::MuCommaLambda);
+/// pop.set_num_offspring(10);
+/// pop.enable_hall_of_fame(5);
+/// pop.add_unrated_individual(TruthTable::new_null(2, 1));
+/// pop.rate_unrated_individuals();
+/// pop.learn_task(100);
+/// let hall_of_fame = pop.hall_of_fame().unwrap();
+/// assert!(hall_of_fame.best_fitness().unwrap() >= pop.best_fitness());
+/// ```
+pub struct HallOfFame<I: Individual> {
+    capacity: usize,
+    /// Ordered by fitness, best first. Among members with the same fitness, the oldest go first.
+    members: Vec< RatedIndividual<I> >,
+    /// Phenotype of each member, in the same order
+    phenotypes: Vec< Vec<bool> >,
+    /// Hash of the phenotype of each member, in the same order, to compare phenotypes quickly
+    hashes: Vec<u64>,
+}
+
+impl<I> HallOfFame<I>
+    where I: Individual + Clone {
+
+    pub fn new(capacity: usize) -> HallOfFame<I> {
+        assert!(capacity > 0);
+        HallOfFame {
+            capacity,
+            members: Vec::with_capacity(capacity),
+            phenotypes: Vec::with_capacity(capacity),
+            hashes: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// The members, best first.
+    pub fn members(&self) -> &[RatedIndividual<I>] {
+        &self.members
+    }
+
+    pub fn best(&self) -> Option<&I> {
+        self.members.first().map(|rated| &rated.indi)
+    }
+
+    pub fn best_fitness(&self) -> Option<i32> {
+        self.members.first().map(|rated| rated.fitness)
+    }
+
+    /// Copies of the num_members best members.
+    pub fn elites(&self, num_members: usize) -> Vec< RatedIndividual<I> > {
+        self.members.iter().take(num_members).map(|rated| RatedIndividual {
+            indi: rated.indi.clone(),
+            fitness: rated.fitness,
+        }).collect()
+    }
+
+    /// Copies of num_members different random members, or of all of them if there are fewer, for example as opponents.
+    pub fn sample(&self, num_members: usize, rng: &mut dyn RngCore) -> Vec<I> {
+        self.members.choose_multiple(rng, num_members).map(|rated| rated.indi.clone()).collect()
+    }
+
+    /// Adds the individual if it is among the best seen so far and better than any member with its phenotype,
+    /// which it replaces. Returns whether it was added.
+    pub fn offer(&mut self, indi: &I, fitness: i32) -> bool {
+        if self.members.len() >= self.capacity && fitness <= self.members[self.members.len() - 1].fitness {
+            return false;
+        }
+        let phenotype = behaviour(indi);
+        let hash = phenotype_hash(&phenotype);
+        // Equal hashes are only a hint, the phenotypes themselves decide
+        if let Some(position) = (0..self.members.len()).find(|&k| self.hashes[k] == hash && self.phenotypes[k] == phenotype) {
+            if fitness <= self.members[position].fitness {
+                return false;
+            }
+            self.members.remove(position);
+            self.phenotypes.remove(position);
+            self.hashes.remove(position);
+        }
+        self.insert(RatedIndividual { indi: indi.clone(), fitness }, phenotype, hash);
+        true
+    }
+
+    /// Offers each of the individuals in order.
+    pub fn offer_all(&mut self, rated_indis: &[RatedIndividual<I>]) {
+        for rated in rated_indis.iter() {
+            self.offer(&rated.indi, rated.fitness);
+        }
+    }
+
+    /// Inserts keeping the members ordered, removing the worst one if the capacity is exceeded.
+    fn insert(&mut self, rated: RatedIndividual<I>, phenotype: Vec<bool>, hash: u64) {
+        let position = self.members.partition_point(|member| member.fitness >= rated.fitness);
+        self.members.insert(position, rated);
+        self.phenotypes.insert(position, phenotype);
+        self.hashes.insert(position, hash);
+        self.members.truncate(self.capacity);
+        self.phenotypes.truncate(self.capacity);
+        self.hashes.truncate(self.capacity);
+    }
+}
+
+fn phenotype_hash(phenotype: &[bool]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    phenotype.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<I> Checkpoint for HallOfFame<I>
+    where I: Individual + Clone + Checkpoint {
+
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("HallOfFame");
+        writer.write(self.capacity);
+        writer.write(self.members.len());
+        for rated in self.members.iter() {
+            writer.new_line();
+            writer.write(rated.fitness);
+            rated.indi.save(writer);
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result< HallOfFame<I> > {
+        reader.expect("HallOfFame")?;
+        let capacity : usize = reader.read()?;
+        let num_members : usize = reader.read()?;
+        if capacity == 0 || num_members > capacity {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid hall of fame size in checkpoint"));
+        }
+        let mut hall_of_fame = HallOfFame::new(capacity);
+        for _ in 0..num_members {
+            let fitness = reader.read()?;
+            let indi = I::load(reader)?;
+            // Already ordered, so pushing keeps the order among members with the same fitness
+            let phenotype = behaviour(&indi);
+            hall_of_fame.hashes.push(phenotype_hash(&phenotype));
+            hall_of_fame.phenotypes.push(phenotype);
+            hall_of_fame.members.push(RatedIndividual { indi, fitness });
+        }
+        Ok(hall_of_fame)
+    }
+}
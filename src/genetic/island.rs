@@ -0,0 +1,229 @@
+//! The island model evolves several populations independently and lets some individuals migrate between them.
+
+use std::hash::Hash;
+
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+use crate::genetic::{
+    Individual,
+    Population,
+    RatedIndividual,
+    Task,
+};
+use crate::genetic::selection::{
+    Selection,
+    TruncationSelection,
+};
+use crate::genetic::termination::{
+    check_criteria,
+    ProgressTracker,
+    Termination,
+    TerminationReason,
+};
+
+/// Which islands send migrants to which other islands.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Topology {
+    /// Each island sends migrants to the next one, and the last one to the first one.
+    Ring,
+    /// Each island sends migrants to all the other islands.
+    FullyConnected,
+}
+
+/// Several populations (islands) evolving independently, with periodic migrations between them.
+/// The tasks of all islands are expected to rate individuals equally, since migrants keep their fitness.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::island::Archipelago;
+/// use tetani::genetic::island::Topology;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let mut islands = vec![];
+/// for _ in 0..4 {
+///     let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2.clone(), 10);
+///     pop.add_unrated_individual(TruthTable::new_null(2, 1));
+///     pop.rate_unrated_individuals();
+///     islands.push(pop);
+/// }
+/// let mut archipelago = Archipelago::new(islands);
+/// archipelago.set_topology(Topology::FullyConnected);
+/// archipelago.set_migration_interval(5);
+/// archipelago.learn_task(100);
+/// ```
+pub struct Archipelago<I: Individual, T: Task> {
+    islands: Vec< Population<I, T> >,
+    topology: Topology,
+    /// Number of generations between migrations
+    migration_interval: usize,
+    /// Number of individuals sent by each island to each of its destinations per migration
+    num_migrants: usize,
+    /// Strategy used to choose the migrants within each island
+    migrant_selection: Box<dyn Selection<I>>,
+    generation: usize,
+    rng: Pcg32,
+}
+
+impl<I, T> Archipelago<I, T>
+    where I: Individual + 'static + Clone + Send + Sync + Hash, T: Task + Sync {
+
+    pub fn new(islands: Vec< Population<I, T> >) -> Archipelago<I, T> {
+        assert!(!islands.is_empty());
+        Archipelago {
+            islands,
+            topology: Topology::Ring,
+            migration_interval: 10,
+            num_migrants: 1,
+            migrant_selection: Box::new(TruncationSelection),
+            generation: 0,
+            rng: Pcg32::from_rng(rand::thread_rng()).unwrap(),
+        }
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn set_migration_interval(&mut self, migration_interval: usize) {
+        assert!(migration_interval > 0);
+        self.migration_interval = migration_interval;
+    }
+
+    /// Islands can't send more migrants than they can hold, and send all their individuals while they have fewer than num_migrants.
+    pub fn set_num_migrants(&mut self, num_migrants: usize) {
+        assert!(self.islands.iter().all(|island| num_migrants <= island.capacity()));
+        self.num_migrants = num_migrants;
+    }
+
+    pub fn set_migrant_selection<S: Selection<I> + 'static>(&mut self, selection: S) {
+        self.migrant_selection = Box::new(selection);
+    }
+
+    /// Seeds the archipelago and each of its islands (with consecutive seeds starting at seed + 1).
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Pcg32::seed_from_u64(seed);
+        for (i, island) in self.islands.iter_mut().enumerate() {
+            island.set_seed(seed.wrapping_add(i as u64 + 1));
+        }
+    }
+
+    pub fn islands(&self) -> &[Population<I, T>] {
+        &self.islands
+    }
+
+    pub fn island_mut(&mut self, index: usize) -> &mut Population<I, T> {
+        &mut self.islands[index]
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Number of evaluations summed for all islands.
+    pub fn num_evaluations(&self) -> usize {
+        self.islands.iter().map(|island| island.num_evaluations()).sum()
+    }
+
+    fn best_island(&self) -> &Population<I, T> {
+        let mut best = &self.islands[0];
+        for island in self.islands.iter() {
+            if island.best_fitness() > best.best_fitness() {
+                best = island;
+            }
+        }
+        best
+    }
+
+    pub fn best_fitness(&self) -> i32 {
+        self.best_island().best_fitness()
+    }
+
+    pub fn best(&self) -> &I {
+        self.best_island().best()
+    }
+
+    fn destinations(&self, source: usize) -> Vec<usize> {
+        let num_islands = self.islands.len();
+        match self.topology {
+            Topology::Ring => vec![(source + 1) % num_islands],
+            Topology::FullyConnected => (0..num_islands).filter(|&i| i != source).collect(),
+        }
+    }
+
+    /// All islands choose their migrants before any of them receives migrants. Migrants are offered to the hall of fame
+    /// of their destination and replace its individuals like offspring do, following its replacement policy.
+    pub fn migrate(&mut self) {
+        let num_islands = self.islands.len();
+        if num_islands < 2 || self.num_migrants == 0 {
+            return;
+        }
+
+        let mut migrations : Vec<(usize, RatedIndividual<I>)> = vec![];
+        for source in 0..num_islands {
+            let island = &self.islands[source];
+            let num_migrants = self.num_migrants.min(island.len());
+            let positions = self.migrant_selection.select(&island.pop, num_migrants, &mut self.rng);
+            for destination in self.destinations(source) {
+                for &position in positions.iter() {
+                    let migrant = &island.pop[position];
+                    migrations.push((destination, RatedIndividual {
+                        indi: migrant.indi.clone(),
+                        fitness: migrant.fitness,
+                    }));
+                }
+            }
+        }
+
+        let mut immigrants : Vec< Vec< RatedIndividual<I> > > = (0..num_islands).map(|_| vec![]).collect();
+        for (destination, migrant) in migrations {
+            immigrants[destination].push(migrant);
+        }
+        for (island, island_immigrants) in self.islands.iter_mut().zip(immigrants) {
+            island.add_immigrants(island_immigrants);
+        }
+    }
+
+    /// Every island produces a generation, and migrations happen every migration_interval generations.
+    pub fn next_generation(&mut self) {
+        for island in self.islands.iter_mut() {
+            island.next_generation();
+        }
+        self.generation += 1;
+        if self.generation.is_multiple_of(self.migration_interval) {
+            self.migrate();
+        }
+    }
+
+    /// Stops as soon as any island reaches the max fitness of its task.
+    pub fn learn_task(&mut self, max_generation: usize) -> TerminationReason {
+        self.learn_task_until(&[Termination::MaxFitness, Termination::MaxGenerations(max_generation)])
+    }
+
+    /// Learns until any of the criteria holds for the whole archipelago, and returns the reason of the first one that holds.
+    /// The best fitness and max fitness are those of the best island, and evaluations are summed for all islands.
+    pub fn learn_task_until(&mut self, criteria: &[Termination]) -> TerminationReason {
+        assert!(!criteria.is_empty());
+        let mut tracker = ProgressTracker::new(self.best_fitness());
+        loop {
+            let best_island = self.best_island();
+            let progress = tracker.progress(self.generation, self.num_evaluations(), best_island.best_fitness(), best_island.task.max_fitness(), best_island.task.is_solved(best_island.best_fitness()));
+            if let Some(reason) = check_criteria(criteria, &progress) {
+                return reason;
+            }
+            self.next_generation();
+            tracker.update(self.best_fitness());
+        }
+    }
+
+    pub fn print(&self) {
+        for (i, island) in self.islands.iter().enumerate() {
+            println!("Island {}, best fitness {}, generation {}", i, island.best_fitness(), island.generation());
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Multi-objective optimization, where individuals trade some objectives against others.
+//! It implements NSGA-II (Non-dominated Sorting Genetic Algorithm II), which approximates the Pareto front.
+
+use rand::{
+    Rng,
+    SeedableRng,
+};
+use rand_pcg::Pcg32;
+
+use crate::genetic::{
+    Crossover,
+    Individual,
+    Task,
+};
+use crate::genetic::termination::{
+    check_criteria,
+    ProgressTracker,
+    Termination,
+    TerminationReason,
+};
+
+/// Individuals are rated several fitnesses (one per objective) for a given task, all of them to be maximized.
+/// Unlike Task, this is implemented for a concrete species so that objectives can depend on its structure.
+pub trait MultiObjectiveTask<I: Individual> {
+    fn calculate_fitnesses(&self, individual: &I) -> Vec<i32>;
+    fn num_objectives(&self) -> usize;
+    /// Best possible fitness of the first objective, which fitness based termination criteria compare with.
+    /// By default the first objective has no known maximum.
+    fn max_fitness(&self) -> i32 {
+        i32::MAX
+    }
+}
+
+/// Individuals that can tell how big or complex they are, for example to prefer smaller circuits.
+pub trait Complexity {
+    fn complexity(&self) -> usize;
+}
+
+/// Multi-objective task maximizing the fitness for a task while minimizing the complexity of the individual.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::ProgrammableLogicArray;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::multiobjective::Nsga2Population;
+/// use tetani::genetic::multiobjective::ParsimonyTask;
+/// let imitate_bi_and_2 = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+/// let task = ParsimonyTask::new(imitate_bi_and_2);
+/// let mut pop : Nsga2Population<ProgrammableLogicArray, ParsimonyTask<ImitationTask<BinaryIndividual>>> = Nsga2Population::new(task, 20);
+/// pop.add_unrated_individual(ProgrammableLogicArray::new_null(2, 1));
+/// pop.rate_unrated_individuals();
+/// pop.learn_task(10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParsimonyTask<T: Task> {
+    task: T,
+}
+
+impl<T> ParsimonyTask<T>
+    where T: Task {
+
+    pub fn new(task: T) -> ParsimonyTask<T> {
+        ParsimonyTask {
+            task,
+        }
+    }
+
+    pub fn task(&self) -> &T {
+        &self.task
+    }
+}
+
+impl<I, T> MultiObjectiveTask<I> for ParsimonyTask<T>
+    where I: Individual + Complexity, T: Task {
+
+    /// The fitness for the task first, the complexity (negated to be maximized) second.
+    fn calculate_fitnesses(&self, individual: &I) -> Vec<i32> {
+        vec![self.task.calculate_fitness(individual), -(individual.complexity() as i32)]
+    }
+
+    fn num_objectives(&self) -> usize {
+        2
+    }
+
+    /// The max fitness for the task.
+    fn max_fitness(&self) -> i32 {
+        self.task.max_fitness()
+    }
+}
+
+/// Returns true if fitnesses is at least as good as other in all objectives and better in at least one.
+pub fn dominates(fitnesses: &[i32], other: &[i32]) -> bool {
+    assert_eq!(fitnesses.len(), other.len());
+    let mut better_in_any = false;
+    for i in 0..fitnesses.len() {
+        if fitnesses[i] < other[i] {
+            return false;
+        }
+        if fitnesses[i] > other[i] {
+            better_in_any = true;
+        }
+    }
+    better_in_any
+}
+
+/// Sorts into fronts: the first front contains the positions of the non dominated fitnesses,
+/// the second one those only dominated by the first front and so on.
+pub fn non_dominated_sort(fitnesses: &[Vec<i32>]) -> Vec< Vec<usize> > {
+    let size = fitnesses.len();
+    let mut dominated_by : Vec< Vec<usize> > = vec![vec![]; size];
+    let mut num_dominating : Vec<usize> = vec![0; size];
+    let mut fronts : Vec< Vec<usize> > = vec![];
+
+    let mut front : Vec<usize> = vec![];
+    for i in 0..size {
+        for j in 0..size {
+            if dominates(&fitnesses[i], &fitnesses[j]) {
+                dominated_by[i].push(j);
+            } else if dominates(&fitnesses[j], &fitnesses[i]) {
+                num_dominating[i] += 1;
+            }
+        }
+        if num_dominating[i] == 0 {
+            front.push(i);
+        }
+    }
+
+    while !front.is_empty() {
+        let mut next_front : Vec<usize> = vec![];
+        for &i in front.iter() {
+            for &j in dominated_by[i].iter() {
+                num_dominating[j] -= 1;
+                if num_dominating[j] == 0 {
+                    next_front.push(j);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next_front;
+    }
+    fronts
+}
+
+/// Crowding distance of each position in the front: how far its neighbours within the front are, summed for all objectives.
+/// The extremes of each objective get an infinite distance so that they are always preferred.
+#[allow(clippy::needless_range_loop)]
+pub fn crowding_distances(fitnesses: &[Vec<i32>], front: &[usize]) -> Vec<f64> {
+    let front_size = front.len();
+    let mut distances : Vec<f64> = vec![0.0; front_size];
+    if front_size == 0 {
+        return distances;
+    }
+    let num_objectives = fitnesses[front[0]].len();
+
+    let mut order : Vec<usize> = (0..front_size).collect();
+    for objective in 0..num_objectives {
+        order.sort_by_key(|&k| fitnesses[front[k]][objective]);
+        let min = fitnesses[front[order[0]]][objective];
+        let max = fitnesses[front[order[front_size - 1]]][objective];
+        distances[order[0]] = f64::INFINITY;
+        distances[order[front_size - 1]] = f64::INFINITY;
+        if max == min {
+            continue;
+        }
+        for k in 1..front_size - 1 {
+            let previous = fitnesses[front[order[k - 1]]][objective];
+            let next = fitnesses[front[order[k + 1]]][objective];
+            distances[order[k]] += (next - previous) as f64 / (max - min) as f64;
+        }
+    }
+    distances
+}
+
+/// An individual together with its fitnesses and its position relative to the Pareto front.
+pub struct MultiRatedIndividual<I: Individual> {
+    pub indi: I,
+    pub fitnesses: Vec<i32>,
+    /// 0 for the non dominated front, 1 for the next front and so on
+    pub rank: usize,
+    pub crowding_distance: f64,
+}
+
+/// NSGA-II population: parents are chosen by binary tournament preferring lower rank and then bigger crowding distance,
+/// and the best capacity individuals among parents and offspring survive.
+pub struct Nsga2Population<I: Individual, T: MultiObjectiveTask<I>> {
+    pub task: T,
+    /// Ordered by rank and, within the same rank, by decreasing crowding distance.
+    pop: Vec< MultiRatedIndividual<I> >,
+    unrated_pop: Vec<I>,
+    capacity: usize,
+    /// Probability of a child being produced by crossover of two parents rather than a copy of one parent
+    crossover_rate: f64,
+    crossover: Crossover,
+    generation: usize,
+    num_evaluations: usize,
+    rng: Pcg32,
+}
+
+impl<I, T> Nsga2Population<I, T>
+    where I: Individual + Clone, T: MultiObjectiveTask<I> {
+
+    pub fn new(task: T, capacity: usize) -> Nsga2Population<I, T> {
+        assert!(capacity > 0);
+        Nsga2Population {
+            task,
+            pop: Vec::with_capacity(capacity * 2),
+            unrated_pop: Vec::with_capacity(capacity),
+            capacity,
+            crossover_rate: 0.0,
+            crossover: Crossover::OnePoint,
+            generation: 0,
+            num_evaluations: 0,
+            rng: Pcg32::from_rng(rand::thread_rng()).unwrap(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pop.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pop.is_empty()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations
+    }
+
+    /// Best fitness of the first objective, the one termination criteria like MaxFitness and Stagnation look at.
+    pub fn best_fitness(&self) -> i32 {
+        self.pop.iter().map(|rated| rated.fitnesses[0]).max().unwrap_or(i32::MIN)
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Pcg32::seed_from_u64(seed);
+    }
+
+    pub fn rng(&mut self) -> &mut Pcg32 {
+        &mut self.rng
+    }
+
+    pub fn set_crossover_rate(&mut self, crossover_rate: f64) {
+        assert!((0.0..=1.0).contains(&crossover_rate));
+        self.crossover_rate = crossover_rate;
+    }
+
+    pub fn set_crossover(&mut self, crossover: Crossover) {
+        self.crossover = crossover;
+    }
+
+    /// All the rated individuals, ordered by rank and crowding distance.
+    pub fn individuals(&self) -> &[MultiRatedIndividual<I>] {
+        &self.pop
+    }
+
+    /// The individuals that no other individual in the population dominates.
+    pub fn pareto_front(&self) -> Vec< &MultiRatedIndividual<I> > {
+        self.pop.iter().filter(|rated| rated.rank == 0).collect()
+    }
+
+    pub fn add_unrated_individual(&mut self, indi: I) {
+        self.unrated_pop.push(indi);
+    }
+
+    fn rate_individual(&mut self, indi: I) -> MultiRatedIndividual<I> {
+        let fitnesses = self.task.calculate_fitnesses(&indi);
+        assert_eq!(self.task.num_objectives(), fitnesses.len());
+        self.num_evaluations += 1;
+        MultiRatedIndividual {
+            indi,
+            fitnesses,
+            rank: 0,
+            crowding_distance: 0.0,
+        }
+    }
+
+    pub fn rate_unrated_individuals(&mut self) {
+        let unrated_pop = std::mem::take(&mut self.unrated_pop);
+        for indi in unrated_pop {
+            let rated = self.rate_individual(indi);
+            self.pop.push(rated);
+        }
+        self.survive();
+    }
+
+    /// Ranks all individuals and keeps the best capacity of them.
+    fn survive(&mut self) {
+        let fitnesses : Vec< Vec<i32> > = self.pop.iter().map(|rated| rated.fitnesses.clone()).collect();
+        let mut order : Vec<usize> = Vec::with_capacity(self.pop.len());
+        for (rank, front) in non_dominated_sort(&fitnesses).iter().enumerate() {
+            let distances = crowding_distances(&fitnesses, front);
+            let mut front_order : Vec<usize> = (0..front.len()).collect();
+            front_order.sort_by(|&a, &b| distances[b].partial_cmp(&distances[a]).unwrap());
+            for k in front_order {
+                self.pop[front[k]].rank = rank;
+                self.pop[front[k]].crowding_distance = distances[k];
+                order.push(front[k]);
+            }
+        }
+        order.truncate(self.capacity);
+
+        let mut old_pop : Vec< Option< MultiRatedIndividual<I> > > = std::mem::take(&mut self.pop).into_iter().map(Some).collect();
+        for position in order {
+            self.pop.push(old_pop[position].take().unwrap());
+        }
+    }
+
+    /// Binary tournament by rank first and crowding distance second.
+    fn tournament(&mut self) -> usize {
+        let a = self.rng.gen_range(0, self.pop.len());
+        let b = self.rng.gen_range(0, self.pop.len());
+        // The population is ordered by rank and crowding distance, so lower positions win
+        a.min(b)
+    }
+
+    pub fn next_generation(&mut self) {
+        assert!(!self.pop.is_empty());
+        let mut offspring : Vec<I> = Vec::with_capacity(self.capacity);
+        for _ in 0..self.capacity {
+            let parent = self.tournament();
+            let mut child = if self.pop.len() > 1 && self.rng.gen_bool(self.crossover_rate) {
+                let partner = self.tournament();
+                self.pop[parent].indi.crossover(&self.pop[partner].indi, &self.crossover, &mut self.rng)
+            } else {
+                self.pop[parent].indi.clone()
+            };
+            child.mutate(&mut self.rng);
+            offspring.push(child);
+        }
+        for child in offspring {
+            let rated = self.rate_individual(child);
+            self.pop.push(rated);
+        }
+        self.survive();
+        self.generation += 1;
+    }
+
+    /// Learns for max_generation generations. Unlike Population::learn_task, reaching the max fitness doesn't stop learning,
+    /// since the other objectives may still improve.
+    pub fn learn_task(&mut self, max_generation: usize) -> TerminationReason {
+        self.learn_task_until(&[Termination::MaxGenerations(max_generation)])
+    }
+
+    /// Learns until any of the criteria holds, and returns the reason of the first one that holds, see Population::learn_task_until.
+    /// Fitness based criteria look at the first objective, see best_fitness and MultiObjectiveTask::max_fitness.
+    pub fn learn_task_until(&mut self, criteria: &[Termination]) -> TerminationReason {
+        assert!(!criteria.is_empty());
+        let mut tracker = ProgressTracker::new(self.best_fitness());
+        loop {
+            let progress = tracker.progress(self.generation, self.num_evaluations, self.best_fitness(), self.task.max_fitness(), self.best_fitness() >= self.task.max_fitness());
+            if let Some(reason) = check_criteria(criteria, &progress) {
+                return reason;
+            }
+            self.next_generation();
+            tracker.update(self.best_fitness());
+        }
+    }
+
+    pub fn print(&self) {
+        for rated in self.pop.iter() {
+            println!("Rank {}, fitnesses {:?}", rated.rank, rated.fitnesses);
+        }
+    }
+}
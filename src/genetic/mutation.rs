@@ -0,0 +1,348 @@
+//! How strongly children are mutated: a fixed number of genes, a probability per gene,
+//! or rates that adapt during evolution (the 1/5th success rule, or per-individual rates carried in the genome).
+
+use std::hash::{
+    Hash,
+    Hasher,
+};
+use std::io;
+
+use rand::{
+    Rng,
+    RngCore,
+};
+use rand::distributions::StandardNormal;
+
+use crate::genetic::{
+    Crossover,
+    Individual,
+};
+use crate::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use crate::genetic::genome::{
+    Gene,
+    Genome,
+};
+use crate::genetic::niching::GenotypeDistance;
+
+/// Mutates num_mutations random genes, chosen independently so the same gene may be mutated more than once.
+pub fn mutate_genes<I: Individual + ?Sized>(indi: &mut I, num_mutations: usize, rng: &mut dyn RngCore) {
+    let num_genes = indi.num_genes();
+    for _ in 0..num_mutations {
+        let gene = rng.gen_range(0, num_genes);
+        indi.mutate_gene(gene, rng);
+    }
+}
+
+/// Mutates each gene independently with the given probability.
+pub fn mutate_genes_with_probability<I: Individual + ?Sized>(indi: &mut I, probability: f64, rng: &mut dyn RngCore) {
+    for gene in 0..indi.num_genes() {
+        if rng.gen_bool(probability) {
+            indi.mutate_gene(gene, rng);
+        }
+    }
+}
+
+/// Rounds a non negative number of mutations up or down randomly, so that the expected number is the given one.
+fn stochastic_round(num_mutations: f64, rng: &mut dyn RngCore) -> usize {
+    let whole = num_mutations.floor();
+    let fraction = num_mutations - whole;
+    whole as usize + if rng.gen_bool(fraction) { 1 } else { 0 }
+}
+
+/// Rechenberg's 1/5th success rule: if more than a fifth of the children improve on their parents,
+/// the mutation strength increases to explore further, otherwise it decreases to refine the current solutions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OneFifthRule {
+    /// Mean number of genes mutated per child
+    strength: f64,
+    /// Multiplies the strength after unsuccessful generations, and divides it after successful ones
+    factor: f64,
+}
+
+impl OneFifthRule {
+    pub fn new(initial_strength: f64) -> OneFifthRule {
+        assert!(initial_strength >= 1.0);
+        OneFifthRule {
+            strength: initial_strength,
+            factor: 0.82,
+        }
+    }
+
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+
+    /// Adapts the strength given how many children of the last generation improved on their parents.
+    fn adapt(&mut self, num_successes: usize, num_children: usize, num_genes: usize) {
+        if num_children == 0 {
+            return;
+        }
+        let success_ratio = num_successes as f64 / num_children as f64;
+        if success_ratio > 0.2 {
+            self.strength /= self.factor;
+        } else if success_ratio < 0.2 {
+            self.strength *= self.factor;
+        }
+        self.strength = self.strength.max(1.0).min(num_genes.max(1) as f64);
+    }
+}
+
+/// Mutation applied to every child produced by a population.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Mutation {
+    /// The individual mutates itself once, see Individual::mutate. This is the default.
+    Single,
+    /// The given number of random genes are mutated.
+    Flips(usize),
+    /// Each gene is mutated with the given probability.
+    PerGene(f64),
+    /// A random number of genes around an adaptive strength are mutated, at least one.
+    OneFifthRule(OneFifthRule),
+}
+
+impl Mutation {
+    pub fn mutate<I: Individual + ?Sized>(&self, indi: &mut I, rng: &mut dyn RngCore) {
+        match *self {
+            Mutation::Single => indi.mutate(rng),
+            Mutation::Flips(num_mutations) => mutate_genes(indi, num_mutations, rng),
+            Mutation::PerGene(probability) => mutate_genes_with_probability(indi, probability, rng),
+            Mutation::OneFifthRule(ref rule) => {
+                let num_mutations = stochastic_round(rule.strength, rng).max(1);
+                mutate_genes(indi, num_mutations, rng);
+            },
+        }
+    }
+
+    /// Whether the parameters are valid: a probability between 0 and 1 for PerGene, and a strength of at least one
+    /// with a factor between 0 and 1 for OneFifthRule.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Mutation::Single | Mutation::Flips(_) => true,
+            Mutation::PerGene(probability) => (0.0..=1.0).contains(&probability),
+            Mutation::OneFifthRule(ref rule) => rule.strength >= 1.0 && rule.factor > 0.0 && rule.factor < 1.0,
+        }
+    }
+
+    /// Adaptive mutations learn from how many children improved on their parents, the rest ignore it.
+    pub fn adapt(&mut self, num_successes: usize, num_children: usize, num_genes: usize) {
+        if let Mutation::OneFifthRule(ref mut rule) = *self {
+            rule.adapt(num_successes, num_children, num_genes);
+        }
+    }
+}
+
+impl Checkpoint for Mutation {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        match *self {
+            Mutation::Single => writer.write("Single"),
+            Mutation::Flips(num_mutations) => {
+                writer.write("Flips");
+                writer.write(num_mutations);
+            },
+            Mutation::PerGene(probability) => {
+                writer.write("PerGene");
+                writer.write(probability);
+            },
+            Mutation::OneFifthRule(ref rule) => {
+                writer.write("OneFifthRule");
+                writer.write(rule.strength);
+                writer.write(rule.factor);
+            },
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Mutation> {
+        let name : String = reader.read()?;
+        let mutation = match name.as_str() {
+            "Single" => Mutation::Single,
+            "Flips" => Mutation::Flips(reader.read()?),
+            "PerGene" => Mutation::PerGene(reader.read()?),
+            "OneFifthRule" => Mutation::OneFifthRule(OneFifthRule {
+                strength: reader.read()?,
+                factor: reader.read()?,
+            }),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown mutation in checkpoint: {}", name))),
+        };
+        if !mutation.is_valid() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid mutation in checkpoint: {:?}", mutation)));
+        }
+        Ok(mutation)
+    }
+}
+
+/// Individual carrying its own mutation rate (the probability of mutating each gene) as part of its genome.
+/// The rate is mutated first, log-normally, and then used to mutate the genes,
+/// so that rates producing good children spread through the population along with them.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::mutation::SelfAdaptive;
+/// let imitate_bi_xor_4 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 4));
+/// let mut pop : Population<SelfAdaptive<TruthTable>, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_4, 10);
+/// pop.add_unrated_individual(SelfAdaptive::new(TruthTable::new_null(4, 2), 0.1));
+/// pop.rate_unrated_individuals();
+/// pop.learn_task(100);
+/// println!("Mutation rate of the best individual: {}", pop.best().rate());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct SelfAdaptive<I: Individual> {
+    indi: I,
+    rate: f64,
+}
+
+impl<I> SelfAdaptive<I>
+    where I: Individual {
+
+    pub fn new(indi: I, rate: f64) -> SelfAdaptive<I> {
+        assert!(rate > 0.0 && rate <= 1.0);
+        SelfAdaptive {
+            indi,
+            rate,
+        }
+    }
+
+    pub fn indi(&self) -> &I {
+        &self.indi
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// The rate can't go below one expected mutation per child.
+    fn min_rate(&self) -> f64 {
+        1.0 / self.indi.num_genes().max(1) as f64
+    }
+}
+
+/// Only the wrapped individual is hashed, since the rate doesn't affect the fitness.
+impl<I> Hash for SelfAdaptive<I>
+    where I: Individual + Hash {
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.indi.hash(state);
+    }
+}
+
+impl<I> Individual for SelfAdaptive<I>
+    where I: Individual {
+
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
+        self.indi.calculate_output(input)
+    }
+
+    fn mutate(&mut self, rng: &mut dyn RngCore) {
+        // Learning rate recommended for a single strategy parameter
+        let tau = 1.0 / (self.indi.num_genes().max(1) as f64).sqrt();
+        let normal : f64 = rng.sample(StandardNormal);
+        self.rate = (self.rate * (tau * normal).exp()).max(self.min_rate()).min(1.0);
+        let mut mutated = false;
+        for gene in 0..self.indi.num_genes() {
+            if rng.gen_bool(self.rate) {
+                self.indi.mutate_gene(gene, rng);
+                mutated = true;
+            }
+        }
+        if !mutated {
+            self.indi.mutate(rng);
+        }
+    }
+
+    /// The child gets the crossover of both genomes and the geometric mean of both rates.
+    fn crossover(&self, other: &SelfAdaptive<I>, crossover: &Crossover, rng: &mut dyn RngCore) -> SelfAdaptive<I> {
+        SelfAdaptive {
+            indi: self.indi.crossover(&other.indi, crossover, rng),
+            rate: (self.rate * other.rate).sqrt(),
+        }
+    }
+
+    fn print(&self) {
+        println!("SelfAdaptive (mutation rate {})", self.rate);
+        self.indi.print();
+    }
+
+    fn input_size(&self) -> usize {
+        self.indi.input_size()
+    }
+
+    fn output_size(&self) -> usize {
+        self.indi.output_size()
+    }
+
+    fn num_genes(&self) -> usize {
+        self.indi.num_genes()
+    }
+
+    fn mutate_gene(&mut self, gene: usize, rng: &mut dyn RngCore) {
+        self.indi.mutate_gene(gene, rng);
+    }
+
+    fn reset_state(&self) -> Vec<bool> {
+        self.indi.reset_state()
+    }
+
+    fn step(&self, input: &[bool], state: &mut Vec<bool>) -> Vec<bool> {
+        self.indi.step(input, state)
+    }
+}
+
+impl<I> Genome for SelfAdaptive<I>
+    where I: Individual + Genome {
+
+    /// The genes of the wrapped individual followed by the rate, as a real gene.
+    fn genes(&self) -> Vec<Gene> {
+        let mut genes = self.indi.genes();
+        genes.push(Gene::Real(self.rate));
+        genes
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        let (rate, indi_genes) = genes.split_last().expect("missing mutation rate gene");
+        let rate = rate.real();
+        assert!(rate > 0.0 && rate <= 1.0);
+        self.indi.set_genes(indi_genes);
+        self.rate = rate;
+    }
+}
+
+/// The distance between the wrapped genomes, ignoring the rates.
+impl<I> GenotypeDistance for SelfAdaptive<I>
+    where I: Individual + GenotypeDistance {
+
+    fn genotype_distance(&self, other: &SelfAdaptive<I>) -> f64 {
+        self.indi.genotype_distance(&other.indi)
+    }
+}
+
+impl<I> Checkpoint for SelfAdaptive<I>
+    where I: Individual + Checkpoint {
+
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("SelfAdaptive");
+        writer.write(self.rate);
+        self.indi.save(writer);
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result< SelfAdaptive<I> > {
+        reader.expect("SelfAdaptive")?;
+        let rate : f64 = reader.read()?;
+        if !(rate > 0.0 && rate <= 1.0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid mutation rate in checkpoint: {}", rate)));
+        }
+        let indi = I::load(reader)?;
+        Ok(SelfAdaptive {
+            indi,
+            rate,
+        })
+    }
+}
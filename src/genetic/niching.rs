@@ -0,0 +1,198 @@
+//! Niching keeps a population spread over several peaks of the fitness landscape instead of converging to a single lineage.
+//! Individuals in the same niche (closer than a radius) compete with each other rather than with the whole population.
+
+use rand::RngCore;
+
+use crate::digital::hamming_distance;
+use crate::genetic::{
+    behaviour,
+    Individual,
+    RatedIndividual,
+};
+use crate::genetic::selection::{
+    spin_roulette,
+    Selection,
+};
+
+/// Individuals whose genomes can be compared, for example counting the bits that differ.
+pub trait GenotypeDistance {
+    fn genotype_distance(&self, other: &Self) -> f64;
+}
+
+/// Number of output bits that differ between two individuals for all the possible inputs: the Hamming distance between their behaviours.
+/// Both individuals must have the same input and output sizes, and the inputs must be few enough to be enumerated.
+pub fn phenotype_distance(indi: &dyn Individual, other: &dyn Individual) -> f64 {
+    assert_eq!(indi.input_size(), other.input_size());
+    assert_eq!(indi.output_size(), other.output_size());
+    hamming_distance(&behaviour(indi), &behaviour(other)) as f64
+}
+
+/// How different two individuals are, to decide whether they belong to the same niche.
+pub trait Distance<I: Individual> {
+    fn distance(&self, indi: &I, other: &I) -> f64;
+}
+
+/// Distance between the genomes, see GenotypeDistance.
+#[derive(Clone, Debug)]
+pub struct Genotypic;
+
+impl<I> Distance<I> for Genotypic
+    where I: Individual + GenotypeDistance {
+
+    fn distance(&self, indi: &I, other: &I) -> f64 {
+        indi.genotype_distance(other)
+    }
+}
+
+/// Distance between the behaviours, see phenotype_distance.
+/// Different genomes computing the same function are at distance zero.
+#[derive(Clone, Debug)]
+pub struct Phenotypic;
+
+impl<I> Distance<I> for Phenotypic
+    where I: Individual {
+
+    fn distance(&self, indi: &I, other: &I) -> f64 {
+        phenotype_distance(indi, other)
+    }
+}
+
+/// Fitness sharing: the fitness of each individual is divided by the number of individuals in its niche,
+/// weighted by how close they are, and individuals are selected proportionally to this shared fitness.
+/// Fitnesses are shifted first so that the worst individual has fitness 1.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::niching::FitnessSharing;
+/// use tetani::genetic::niching::Phenotypic;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 10);
+/// pop.add_unrated_individual(TruthTable::new_null(2, 1));
+/// pop.rate_unrated_individuals();
+/// pop.set_selection(FitnessSharing::new(2.0, Phenotypic));
+/// pop.set_num_selected(4);
+/// pop.learn_task(100);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FitnessSharing<D> {
+    /// Individuals at this distance or further don't share fitness
+    radius: f64,
+    /// Shape of the sharing function, 1.0 makes it decrease linearly with the distance
+    alpha: f64,
+    distance: D,
+}
+
+impl<D> FitnessSharing<D> {
+    pub fn new(radius: f64, distance: D) -> FitnessSharing<D> {
+        assert!(radius > 0.0);
+        FitnessSharing {
+            radius,
+            alpha: 1.0,
+            distance,
+        }
+    }
+
+    pub fn set_alpha(&mut self, alpha: f64) {
+        assert!(alpha > 0.0);
+        self.alpha = alpha;
+    }
+
+    fn sharing(&self, distance: f64) -> f64 {
+        if distance < self.radius {
+            1.0 - (distance / self.radius).powf(self.alpha)
+        } else {
+            0.0
+        }
+    }
+
+    /// Shared fitness of each individual of the population.
+    pub fn shared_fitnesses<I>(&self, pop: &[RatedIndividual<I>]) -> Vec<f64>
+        where I: Individual, D: Distance<I> {
+
+        let size = pop.len();
+        let min_fitness = pop.iter().map(|rated| rated.fitness).min().unwrap_or(0);
+        let mut niche_counts : Vec<f64> = vec![0.0; size];
+        for i in 0..size {
+            // The distance of an individual to itself is zero, so niche counts are at least 1
+            niche_counts[i] += 1.0;
+            for j in i + 1..size {
+                let sharing = self.sharing(self.distance.distance(&pop[i].indi, &pop[j].indi));
+                niche_counts[i] += sharing;
+                niche_counts[j] += sharing;
+            }
+        }
+        (0..size).map(|i| (pop[i].fitness - min_fitness + 1) as f64 / niche_counts[i]).collect()
+    }
+}
+
+impl<I, D> Selection<I> for FitnessSharing<D>
+    where I: Individual, D: Distance<I> {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        let shared_fitnesses = self.shared_fitnesses(pop);
+        spin_roulette(&shared_fitnesses, num_selected, rng)
+    }
+}
+
+/// Clearing: only the best individuals of each niche (its winners) keep their fitness,
+/// the rest are cleared and can't be selected. The winners are then selected by another selection strategy.
+///
+/// Since the population is ordered best first, each individual is a winner unless
+/// niche_capacity better winners are closer to it than the radius.
+#[derive(Clone, Debug)]
+pub struct Clearing<S, D> {
+    radius: f64,
+    /// Number of winners per niche
+    niche_capacity: usize,
+    distance: D,
+    selection: S,
+}
+
+impl<S, D> Clearing<S, D> {
+    pub fn new(radius: f64, niche_capacity: usize, distance: D, selection: S) -> Clearing<S, D> {
+        assert!(niche_capacity > 0);
+        Clearing {
+            radius,
+            niche_capacity,
+            distance,
+            selection,
+        }
+    }
+
+    /// Positions of the winners in a population ordered best first.
+    pub fn winners<I>(&self, pop: &[RatedIndividual<I>]) -> Vec<usize>
+        where I: Individual, D: Distance<I> {
+
+        let mut winners : Vec<usize> = vec![];
+        for i in 0..pop.len() {
+            let num_close_winners = winners.iter()
+                .filter(|&&winner| self.distance.distance(&pop[winner].indi, &pop[i].indi) < self.radius)
+                .count();
+            if num_close_winners < self.niche_capacity {
+                winners.push(i);
+            }
+        }
+        winners
+    }
+}
+
+impl<I, S, D> Selection<I> for Clearing<S, D>
+    where I: Individual + Clone, S: Selection<I>, D: Distance<I> {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        let winners = self.winners(pop);
+        let winners_pop : Vec< RatedIndividual<I> > = winners.iter().map(|&position| RatedIndividual {
+            indi: pop[position].indi.clone(),
+            fitness: pop[position].fitness,
+        }).collect();
+        // Fewer winners than requested are selected again
+        let selected = self.selection.select(&winners_pop, num_selected.min(winners_pop.len()), rng);
+        (0..num_selected).map(|k| winners[selected[k % selected.len()]]).collect()
+    }
+}
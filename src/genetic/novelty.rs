@@ -0,0 +1,179 @@
+//! Novelty search rewards individuals for behaving differently from what has been seen before,
+//! which helps to escape the local optima of deceptive tasks.
+
+use std::sync::Mutex;
+
+use rand::RngCore;
+
+use crate::digital::hamming_distance;
+use crate::genetic::{
+    behaviour,
+    Individual,
+    Task,
+};
+
+/// Fitnesses are the blended score multiplied by this, to keep some decimals in an integer fitness.
+pub const NOVELTY_FITNESS_SCALE: f64 = 1000.0;
+
+/// Task rewarding the novelty of the behaviour of individuals: the mean Hamming distance
+/// from their behaviour to the closest behaviours of an archive of previously seen behaviours.
+/// The novelty can be blended with the fitness for an objective task, and the resulting score
+/// (in bits, like the fitness of ImitationTask) is multiplied by NOVELTY_FITNESS_SCALE.
+///
+/// The archive doesn't change while individuals are rated. Before each generation, the behaviours of the population
+/// novel enough are added to it in population order (see Task::observe_population), and the population is rated again.
+/// So fitnesses don't depend on the order in which individuals are rated, and the fitness cache and
+/// several threads can be used.
+///
+/// Novelty has no maximum, so max_fitness is i32::MAX and the task is solved (see Task::is_solved) when
+/// any individual has reached the max fitness of the objective.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::novelty::NoveltyTask;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let mut novelty_task = NoveltyTask::new(imitate_bi_xor_2);
+/// novelty_task.set_objective_weight(0.5);
+/// let mut pop : Population<TruthTable, NoveltyTask<ImitationTask<BinaryIndividual>>> = Population::new(novelty_task, 10);
+/// pop.add_unrated_individual(TruthTable::new_null(2, 1));
+/// pop.rate_unrated_individuals();
+/// pop.learn_task(1000);
+/// println!("Archived behaviours: {}", pop.task.archive_len());
+/// ```
+pub struct NoveltyTask<T: Task> {
+    objective: T,
+    /// Weight of the objective fitness in the blend, 0.0 for pure novelty search
+    objective_weight: f64,
+    /// Number of closest archived behaviours averaged to calculate the novelty
+    num_neighbors: usize,
+    /// Minimum novelty for a behaviour to be archived
+    archive_threshold: f64,
+    archive: Vec< Vec<bool> >,
+    /// Best fitness for the objective among the individuals rated so far
+    best_objective_fitness: Mutex< Option<i32> >,
+}
+
+impl<T> NoveltyTask<T>
+    where T: Task {
+
+    /// Pure novelty search (objective weight 0), averaging the 10 closest behaviours, archiving behaviours with novelty 1 or more.
+    pub fn new(objective: T) -> NoveltyTask<T> {
+        NoveltyTask {
+            objective,
+            objective_weight: 0.0,
+            num_neighbors: 10,
+            archive_threshold: 1.0,
+            archive: vec![],
+            best_objective_fitness: Mutex::new(None),
+        }
+    }
+
+    pub fn objective(&self) -> &T {
+        &self.objective
+    }
+
+    pub fn set_objective_weight(&mut self, objective_weight: f64) {
+        assert!((0.0..=1.0).contains(&objective_weight));
+        self.objective_weight = objective_weight;
+    }
+
+    pub fn set_num_neighbors(&mut self, num_neighbors: usize) {
+        assert!(num_neighbors > 0);
+        self.num_neighbors = num_neighbors;
+    }
+
+    pub fn set_archive_threshold(&mut self, archive_threshold: f64) {
+        self.archive_threshold = archive_threshold;
+    }
+
+    pub fn archive_len(&self) -> usize {
+        self.archive.len()
+    }
+
+    /// Best fitness for the objective among all the individuals rated so far.
+    pub fn best_objective_fitness(&self) -> Option<i32> {
+        *self.best_objective_fitness.lock().unwrap()
+    }
+
+    /// Whether any individual rated so far has reached the max fitness of the objective.
+    pub fn solved(&self) -> bool {
+        match self.best_objective_fitness() {
+            Some(fitness) => fitness >= self.objective.max_fitness(),
+            None => false,
+        }
+    }
+
+    /// Adds the behaviours novel enough to the archive, in order, each one counting for the novelty of the next ones.
+    /// Returns whether any behaviour was added.
+    pub fn archive_behaviours(&mut self, individuals: &[&dyn Individual]) -> bool {
+        let archive_len = self.archive.len();
+        for indi in individuals.iter() {
+            let behaviour = behaviour(*indi);
+            if self.novelty(&behaviour) >= self.archive_threshold {
+                self.archive.push(behaviour);
+            }
+        }
+        self.archive.len() > archive_len
+    }
+
+    /// Mean distance to the closest archived behaviours, or the length of the behaviour if the archive is empty.
+    fn novelty(&self, behaviour: &[bool]) -> f64 {
+        if self.archive.is_empty() {
+            return behaviour.len() as f64;
+        }
+        let mut distances : Vec<usize> = self.archive.iter().map(|archived| hamming_distance(behaviour, archived)).collect();
+        distances.sort_unstable();
+        let num_neighbors = self.num_neighbors.min(distances.len());
+        distances[..num_neighbors].iter().sum::<usize>() as f64 / num_neighbors as f64
+    }
+}
+
+impl<T> Task for NoveltyTask<T>
+    where T: Task {
+
+    fn calculate_fitness(&self, indi: &dyn Individual) -> i32 {
+        // Also calculated for pure novelty search, to know when the objective is solved
+        let objective_fitness = self.objective.calculate_fitness(indi);
+        let novelty = self.novelty(&behaviour(indi));
+        {
+            let mut best_objective_fitness = self.best_objective_fitness.lock().unwrap();
+            if best_objective_fitness.is_none_or(|best| objective_fitness > best) {
+                *best_objective_fitness = Some(objective_fitness);
+            }
+        }
+
+        let score = (1.0 - self.objective_weight) * novelty + self.objective_weight * objective_fitness as f64;
+        (score * NOVELTY_FITNESS_SCALE).round() as i32
+    }
+
+    /// Novelty has no maximum.
+    fn max_fitness(&self) -> i32 {
+        i32::MAX
+    }
+
+    /// The cases of the objective, since novelty has no cases of its own.
+    fn calculate_case_errors(&self, indi: &dyn Individual) -> Vec<i32> {
+        self.objective.calculate_case_errors(indi)
+    }
+
+    /// Solved when the objective is, see solved.
+    fn is_solved(&self, _best_fitness: i32) -> bool {
+        self.solved()
+    }
+
+    fn prepare_generation(&mut self, rng: &mut dyn RngCore) -> bool {
+        self.objective.prepare_generation(rng)
+    }
+
+    /// Archives the behaviours of the population, see archive_behaviours.
+    fn observe_population(&mut self, population: &[&dyn Individual]) -> bool {
+        let objective_changed = self.objective.observe_population(population);
+        self.archive_behaviours(population) || objective_changed
+    }
+}
@@ -0,0 +1,133 @@
+//! Watching the evolution of a population generation after generation.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use crate::genetic::{
+    genome_hash,
+    Individual,
+    RatedIndividual,
+};
+
+/// Summary of a population after a generation.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best_fitness: i32,
+    pub mean_fitness: f64,
+    pub median_fitness: f64,
+    pub worst_fitness: i32,
+    /// Number of different genomes in the population, a measure of its diversity
+    pub num_distinct_genomes: usize,
+    /// Number of times the task has calculated the fitness of an individual so far
+    pub num_evaluations: usize,
+}
+
+impl GenerationStats {
+    /// Calculates the stats of a non empty population ordered by fitness, best first.
+    pub fn new<I: Individual + Hash>(pop: &[RatedIndividual<I>], generation: usize, num_evaluations: usize) -> GenerationStats {
+        assert!(!pop.is_empty());
+        let size = pop.len();
+        let sum : i64 = pop.iter().map(|rated| rated.fitness as i64).sum();
+        let median_fitness = if size % 2 == 1 {
+            pop[size / 2].fitness as f64
+        } else {
+            (pop[size / 2 - 1].fitness as f64 + pop[size / 2].fitness as f64) / 2.0
+        };
+        let genomes : HashSet<u64> = pop.iter().map(|rated| genome_hash(&rated.indi)).collect();
+        GenerationStats {
+            generation,
+            best_fitness: pop[0].fitness,
+            mean_fitness: sum as f64 / size as f64,
+            median_fitness,
+            worst_fitness: pop[size - 1].fitness,
+            num_distinct_genomes: genomes.len(),
+            num_evaluations,
+        }
+    }
+
+    /// Header of the comma separated values written by to_csv_line.
+    pub fn csv_header() -> &'static str {
+        "generation,best_fitness,mean_fitness,median_fitness,worst_fitness,num_distinct_genomes,num_evaluations"
+    }
+
+    pub fn to_csv_line(&self) -> String {
+        format!("{},{},{},{},{},{},{}", self.generation, self.best_fitness, self.mean_fitness, self.median_fitness,
+            self.worst_fitness, self.num_distinct_genomes, self.num_evaluations)
+    }
+}
+
+/// Gets notified after every generation of a population.
+/// Any closure taking the stats can be used as an observer.
+pub trait Observer {
+    fn observe(&mut self, stats: &GenerationStats);
+}
+
+impl<F> Observer for F
+    where F: FnMut(&GenerationStats) {
+
+    fn observe(&mut self, stats: &GenerationStats) {
+        self(stats)
+    }
+}
+
+/// Observer that stores the stats of every generation.
+/// Clones share the same history, so a clone can be kept to read the history after adding the recorder to a population.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::observer::HistoryRecorder;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 10);
+/// pop.add_unrated_individual(TruthTable::new_null(2, 1));
+/// pop.rate_unrated_individuals();
+/// let recorder = HistoryRecorder::new();
+/// pop.add_observer(recorder.clone());
+/// pop.learn_task(100);
+/// assert_eq!(pop.generation(), recorder.history().len());
+/// println!("{}", recorder.to_csv());
+/// ```
+#[derive(Clone, Default)]
+pub struct HistoryRecorder {
+    history: Arc< Mutex< Vec<GenerationStats> > >,
+}
+
+impl HistoryRecorder {
+    pub fn new() -> HistoryRecorder {
+        HistoryRecorder {
+            history: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// The stats of all the generations observed so far, oldest first.
+    pub fn history(&self) -> Vec<GenerationStats> {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// The whole history as comma separated values, with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(GenerationStats::csv_header());
+        csv.push('\n');
+        for stats in self.history.lock().unwrap().iter() {
+            csv.push_str(&stats.to_csv_line());
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl Observer for HistoryRecorder {
+    fn observe(&mut self, stats: &GenerationStats) {
+        self.history.lock().unwrap().push(stats.clone());
+    }
+}
@@ -0,0 +1,345 @@
+//! Selection strategies choose which individuals of a Population become parents of the next generation.
+
+use rand::{
+    Rng,
+    RngCore,
+};
+use rand::seq::SliceRandom;
+
+use crate::genetic::{
+    Individual,
+    RatedIndividual,
+};
+
+/// Chooses parents among the rated individuals of a population.
+pub trait Selection<I: Individual> {
+    /// Returns num_selected positions within pop, which is ordered by fitness (best first).
+    /// The same position can be returned more than once.
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize>;
+
+    /// Whether the population must pass the errors of its individuals on each test case, see select_by_cases.
+    fn uses_case_errors(&self) -> bool {
+        false
+    }
+
+    /// Like select, also given the errors of each individual on each test case of the task (see Task::calculate_case_errors).
+    /// Only called if uses_case_errors returns true.
+    fn select_by_cases(&self, pop: &[RatedIndividual<I>], _case_errors: &[Vec<i32>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        self.select(pop, num_selected, rng)
+    }
+}
+
+/// Selects the best num_selected individuals.
+/// This is the default selection strategy of a Population.
+#[derive(Clone, Debug)]
+pub struct TruncationSelection;
+
+impl<I> Selection<I> for TruncationSelection
+    where I: Individual {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, _rng: &mut dyn RngCore) -> Vec<usize> {
+        assert!(pop.len() >= num_selected);
+        (0..num_selected).collect()
+    }
+}
+
+/// Each selected individual is the winner of a tournament between tournament_size random individuals.
+///
+/// The best contestant wins with probability pressure, if it doesn't, the second best wins with probability pressure
+/// and so on. With pressure 1.0 the best contestant always wins, which makes the selection deterministic.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::selection::TournamentSelection;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 100);
+/// pop.set_selection(TournamentSelection::new(3, 0.8));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TournamentSelection {
+    tournament_size: usize,
+    pressure: f64,
+}
+
+impl TournamentSelection {
+
+    pub fn new(tournament_size: usize, pressure: f64) -> TournamentSelection {
+        assert!(tournament_size > 0);
+        assert!(pressure > 0.0 && pressure <= 1.0);
+        TournamentSelection {
+            tournament_size,
+            pressure,
+        }
+    }
+}
+
+impl<I> Selection<I> for TournamentSelection
+    where I: Individual {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        assert!(!pop.is_empty());
+        let mut selected = Vec::with_capacity(num_selected);
+        let mut contestants : Vec<usize> = Vec::with_capacity(self.tournament_size);
+        for _ in 0..num_selected {
+            contestants.clear();
+            for _ in 0..self.tournament_size {
+                contestants.push(rng.gen_range(0, pop.len()));
+            }
+            // The population is ordered by fitness, so lower positions are better contestants
+            contestants.sort();
+
+            let mut winner = contestants[contestants.len() - 1];
+            for &contestant in contestants.iter() {
+                if rng.gen_bool(self.pressure) {
+                    winner = contestant;
+                    break;
+                }
+            }
+            selected.push(winner);
+        }
+        selected
+    }
+}
+
+/// Fitnesses shifted so that the worst individual has weight 0.0, or all weights 1.0 if they are all equal.
+fn proportionate_weights<I: Individual>(pop: &[RatedIndividual<I>]) -> Vec<f64> {
+    let min_fitness = pop.iter().map(|rated| rated.fitness).min().unwrap();
+    let mut weights : Vec<f64> = pop.iter().map(|rated| (rated.fitness - min_fitness) as f64).collect();
+    if weights.iter().all(|&weight| weight == 0.0) {
+        for weight in weights.iter_mut() {
+            *weight = 1.0;
+        }
+    }
+    weights
+}
+
+/// Returns the position whose cumulative weight interval contains the given point.
+fn find_cumulative(weights: &[f64], point: f64) -> usize {
+    let mut cumulative = 0.0;
+    for (i, weight) in weights.iter().enumerate() {
+        cumulative += weight;
+        if point < cumulative {
+            return i;
+        }
+    }
+    // Rounding errors may leave the point right at the end
+    weights.len() - 1
+}
+
+/// Spins a roulette wheel num_selected times.
+pub(crate) fn spin_roulette(weights: &[f64], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+    let total_weight : f64 = weights.iter().sum();
+    let mut selected = Vec::with_capacity(num_selected);
+    for _ in 0..num_selected {
+        let point = rng.gen_range(0.0, total_weight);
+        selected.push(find_cumulative(weights, point));
+    }
+    selected
+}
+
+/// Fitness-proportionate selection: the probability of being selected is proportional to fitness.
+///
+/// Since fitness may be negative, fitnesses are shifted so that the worst individual has no chance of being selected
+/// (unless all individuals are equally fit, then all have the same chance).
+#[derive(Clone, Debug)]
+pub struct RouletteWheelSelection;
+
+impl<I> Selection<I> for RouletteWheelSelection
+    where I: Individual {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        assert!(!pop.is_empty());
+        spin_roulette(&proportionate_weights(pop), num_selected, rng)
+    }
+}
+
+/// Like RouletteWheelSelection but with a single spin of a wheel with num_selected equally spaced pointers.
+/// This guarantees that the number of times an individual is selected is close to its expected value.
+#[derive(Clone, Debug)]
+pub struct StochasticUniversalSampling;
+
+impl<I> Selection<I> for StochasticUniversalSampling
+    where I: Individual {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        assert!(!pop.is_empty());
+        let weights = proportionate_weights(pop);
+        let total_weight : f64 = weights.iter().sum();
+        let pointer_distance = total_weight / num_selected as f64;
+        let start = rng.gen_range(0.0, pointer_distance);
+
+        let mut selected = Vec::with_capacity(num_selected);
+        for i in 0..num_selected {
+            selected.push(find_cumulative(&weights, start + i as f64 * pointer_distance));
+        }
+        selected
+    }
+}
+
+/// The probability of being selected decreases linearly with the rank of the individual,
+/// regardless of how big the fitness differences are.
+///
+/// The pressure (between 1.0 and 2.0) is the expected number of times the best individual is selected
+/// per individual in the population. With pressure 1.0 all individuals have the same chance,
+/// with pressure 2.0 the worst individual has no chance.
+#[derive(Clone, Debug)]
+pub struct LinearRankSelection {
+    pressure: f64,
+}
+
+impl LinearRankSelection {
+
+    pub fn new(pressure: f64) -> LinearRankSelection {
+        assert!((1.0..=2.0).contains(&pressure));
+        LinearRankSelection {
+            pressure,
+        }
+    }
+}
+
+impl<I> Selection<I> for LinearRankSelection
+    where I: Individual {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        assert!(!pop.is_empty());
+        let pop_size = pop.len();
+        if pop_size == 1 {
+            return vec![0; num_selected];
+        }
+        // The population is already ordered by fitness, so the rank is the position
+        let mut weights : Vec<f64> = Vec::with_capacity(pop_size);
+        for rank in 0..pop_size {
+            let relative_rank = rank as f64 / (pop_size - 1) as f64;
+            weights.push(self.pressure - (2.0 * self.pressure - 2.0) * relative_rank);
+        }
+        spin_roulette(&weights, num_selected, rng)
+    }
+}
+
+/// How much worse than the best candidate on a case a candidate may be and still survive lexicase selection.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Epsilon {
+    /// The same tolerance for every case, 0 for plain lexicase selection.
+    Fixed(i32),
+    /// The median absolute deviation of the errors of the population on each case,
+    /// which adapts to how spread the errors are. This is epsilon-lexicase selection.
+    MedianAbsoluteDeviation,
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+/// Lexicase selection: each parent is chosen by filtering the whole population through the test cases of the task
+/// in a random order, keeping only the candidates with the lowest error on each case, until one candidate remains
+/// or the cases run out (then a random remaining candidate is chosen).
+/// Individuals solving hard cases that most others fail get selected even if their total fitness is low.
+///
+/// The population calculates the errors on each case with Task::calculate_case_errors. Used where case errors aren't
+/// available (for example within Clearing), the whole task is a single case and the best individual is always chosen.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::selection::Epsilon;
+/// use tetani::genetic::selection::LexicaseSelection;
+/// let imitate_bi_xor_4 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 4));
+/// let mut pop : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_4, 20);
+/// let mut lexicase = LexicaseSelection::new();
+/// lexicase.set_epsilon(Epsilon::MedianAbsoluteDeviation);
+/// pop.set_selection(lexicase);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LexicaseSelection {
+    epsilon: Epsilon,
+}
+
+impl LexicaseSelection {
+
+    /// Plain lexicase selection, only the candidates with the lowest error survive each case.
+    pub fn new() -> LexicaseSelection {
+        LexicaseSelection {
+            epsilon: Epsilon::Fixed(0),
+        }
+    }
+
+    pub fn set_epsilon(&mut self, epsilon: Epsilon) {
+        if let Epsilon::Fixed(tolerance) = epsilon {
+            assert!(tolerance >= 0);
+        }
+        self.epsilon = epsilon;
+    }
+
+    /// Tolerance for each case.
+    fn case_epsilons(&self, case_errors: &[Vec<i32>], num_cases: usize) -> Vec<f64> {
+        match self.epsilon {
+            Epsilon::Fixed(tolerance) => vec![tolerance as f64; num_cases],
+            Epsilon::MedianAbsoluteDeviation => (0..num_cases).map(|case| {
+                let mut errors : Vec<f64> = case_errors.iter().map(|errors| errors[case] as f64).collect();
+                let median_error = median(&mut errors);
+                let mut deviations : Vec<f64> = errors.iter().map(|error| (error - median_error).abs()).collect();
+                median(&mut deviations)
+            }).collect(),
+        }
+    }
+}
+
+impl Default for LexicaseSelection {
+    fn default() -> LexicaseSelection {
+        LexicaseSelection::new()
+    }
+}
+
+impl<I> Selection<I> for LexicaseSelection
+    where I: Individual {
+
+    fn select(&self, pop: &[RatedIndividual<I>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        let case_errors : Vec< Vec<i32> > = pop.iter().map(|rated| vec![-rated.fitness]).collect();
+        self.select_by_cases(pop, &case_errors, num_selected, rng)
+    }
+
+    fn uses_case_errors(&self) -> bool {
+        true
+    }
+
+    fn select_by_cases(&self, pop: &[RatedIndividual<I>], case_errors: &[Vec<i32>], num_selected: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+        assert!(!pop.is_empty());
+        assert_eq!(pop.len(), case_errors.len());
+        let num_cases = case_errors[0].len();
+        let epsilons = self.case_epsilons(case_errors, num_cases);
+
+        let mut cases : Vec<usize> = (0..num_cases).collect();
+        let mut selected = Vec::with_capacity(num_selected);
+        for _ in 0..num_selected {
+            cases.shuffle(rng);
+            let mut candidates : Vec<usize> = (0..pop.len()).collect();
+            for &case in cases.iter() {
+                if candidates.len() == 1 {
+                    break;
+                }
+                let best_error = candidates.iter().map(|&candidate| case_errors[candidate][case]).min().unwrap();
+                let max_error = best_error as f64 + epsilons[case];
+                candidates.retain(|&candidate| case_errors[candidate][case] as f64 <= max_error);
+            }
+            selected.push(*candidates.choose(rng).unwrap());
+        }
+        selected
+    }
+}
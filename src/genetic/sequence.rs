@@ -0,0 +1,307 @@
+//! Tasks on sequences of inputs, for stateful individuals whose output depends on the inputs they have seen before,
+//! like serial adders or sequence detectors. See Individual::reset_state and Individual::step.
+
+use std::io;
+
+use rand::{
+    RngCore,
+    SeedableRng,
+};
+use rand_pcg::Pcg32;
+
+use crate::digital::get_rand_bitvector;
+use crate::genetic::{
+    calculate_fitness_result,
+    Crossover,
+    Individual,
+    Task,
+};
+use crate::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use crate::genetic::genome::{
+    Gene,
+    Genome,
+};
+use crate::genetic::niching::GenotypeDistance;
+
+/// Inputs fed one per step to an individual from its reset state, and the outputs expected at each step.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Sequence {
+    pub inputs: Vec< Vec<bool> >,
+    pub outputs: Vec< Vec<bool> >,
+}
+
+impl Sequence {
+    /// The outputs of the individual for the inputs, stepping from its reset state.
+    pub fn from_individual(indi: &dyn Individual, inputs: Vec< Vec<bool> >) -> Sequence {
+        let outputs = run_sequence(indi, &inputs);
+        Sequence {
+            inputs,
+            outputs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+/// The outputs of the individual for each input, stepping from its reset state.
+pub fn run_sequence(indi: &dyn Individual, inputs: &[Vec<bool>]) -> Vec< Vec<bool> > {
+    let mut state = indi.reset_state();
+    inputs.iter().map(|input| indi.step(input, &mut state)).collect()
+}
+
+/// num_sequences random sequences of length inputs of in_size bits, drawn from the seed.
+pub fn random_input_sequences(in_size: usize, num_sequences: usize, length: usize, seed: u64) -> Vec< Vec< Vec<bool> > > {
+    let mut rng = Pcg32::seed_from_u64(seed);
+    (0..num_sequences).map(|_| (0..length).map(|_| get_rand_bitvector(in_size, &mut rng)).collect()).collect()
+}
+
+/// Task to reproduce the outputs of sequences, one fitness point per output bit right at each step.
+/// Each sequence is fed from the reset state of the individual, so its state carries over between the steps of a sequence
+/// but not between sequences.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::SerialAdder;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::Population;
+/// use tetani::genetic::sequence::random_input_sequences;
+/// use tetani::genetic::sequence::Recurrent;
+/// use tetani::genetic::sequence::SequenceTask;
+/// let inputs = random_input_sequences(2, 16, 8, 0);
+/// let serial_adder_task = SequenceTask::imitate(&SerialAdder::new(), inputs);
+/// // Two input bits and one bit of state in, one output bit and one bit of state out
+/// let mut pop : Population<Recurrent<TruthTable>, SequenceTask> = Population::new(serial_adder_task, 16);
+/// pop.add_unrated_individual(Recurrent::new(TruthTable::new_null(3, 2), 1));
+/// pop.rate_unrated_individuals();
+/// pop.learn_task(1000);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct SequenceTask {
+    sequences: Vec<Sequence>,
+}
+
+impl SequenceTask {
+    /// All the sequences must have inputs and outputs of the same sizes.
+    pub fn new(sequences: Vec<Sequence>) -> SequenceTask {
+        assert!(!sequences.is_empty());
+        let in_size = sequences[0].inputs.first().map(|input| input.len());
+        let out_size = sequences[0].outputs.first().map(|output| output.len());
+        for sequence in sequences.iter() {
+            assert_eq!(sequence.inputs.len(), sequence.outputs.len());
+            assert!(sequence.inputs.iter().all(|input| Some(input.len()) == in_size));
+            assert!(sequence.outputs.iter().all(|output| Some(output.len()) == out_size));
+        }
+        SequenceTask {
+            sequences,
+        }
+    }
+
+    /// Sequences with the outputs of the given stateful individual for each of the input sequences.
+    pub fn imitate(reference: &dyn Individual, input_sequences: Vec< Vec< Vec<bool> > >) -> SequenceTask {
+        SequenceTask::new(input_sequences.into_iter().map(|inputs| Sequence::from_individual(reference, inputs)).collect())
+    }
+
+    pub fn sequences(&self) -> &[Sequence] {
+        &self.sequences
+    }
+
+    /// Output bits right over all the steps of a sequence.
+    fn sequence_fitness(&self, sequence: &Sequence, indi: &dyn Individual) -> i32 {
+        let outputs = run_sequence(indi, &sequence.inputs);
+        (0..sequence.len()).map(|step| calculate_fitness_result(&sequence.outputs[step], &outputs[step])).sum()
+    }
+
+    fn sequence_max_fitness(&self, sequence: &Sequence) -> i32 {
+        sequence.outputs.iter().map(|output| output.len() as i32).sum()
+    }
+}
+
+impl Checkpoint for SequenceTask {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("SequenceTask");
+        writer.write(self.sequences.len());
+        for sequence in self.sequences.iter() {
+            writer.new_line();
+            writer.write(sequence.len());
+            for step in 0..sequence.len() {
+                writer.write_bools(&sequence.inputs[step]);
+                writer.write_bools(&sequence.outputs[step]);
+            }
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<SequenceTask> {
+        reader.expect("SequenceTask")?;
+        let num_sequences : usize = reader.read()?;
+        let mut sequences = Vec::with_capacity(num_sequences);
+        for _ in 0..num_sequences {
+            let length : usize = reader.read()?;
+            let mut sequence = Sequence {
+                inputs: Vec::with_capacity(length),
+                outputs: Vec::with_capacity(length),
+            };
+            for _ in 0..length {
+                sequence.inputs.push(reader.read_bools()?);
+                sequence.outputs.push(reader.read_bools()?);
+            }
+            sequences.push(sequence);
+        }
+        Ok(SequenceTask {
+            sequences,
+        })
+    }
+}
+
+impl Task for SequenceTask {
+    fn calculate_fitness(&self, indi: &dyn Individual) -> i32 {
+        self.sequences.iter().map(|sequence| self.sequence_fitness(sequence, indi)).sum()
+    }
+
+    fn max_fitness(&self) -> i32 {
+        self.sequences.iter().map(|sequence| self.sequence_max_fitness(sequence)).sum()
+    }
+
+    /// One case per sequence, with one error per output bit wrong.
+    fn calculate_case_errors(&self, indi: &dyn Individual) -> Vec<i32> {
+        self.sequences.iter()
+            .map(|sequence| self.sequence_max_fitness(sequence) - self.sequence_fitness(sequence, indi))
+            .collect()
+    }
+}
+
+/// Turns a stateless individual into a stateful one by feeding some of its outputs back as inputs at the next step.
+/// The wrapped individual gets the input followed by the state, and outputs the output followed by the next state,
+/// so it has state_size more inputs and outputs than the recurrent individual. The state starts with all bits false.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Recurrent<I: Individual> {
+    indi: I,
+    state_size: usize,
+}
+
+impl<I> Recurrent<I>
+    where I: Individual {
+
+    pub fn new(indi: I, state_size: usize) -> Recurrent<I> {
+        assert!(indi.input_size() >= state_size);
+        assert!(indi.output_size() >= state_size);
+        Recurrent {
+            indi,
+            state_size,
+        }
+    }
+
+    pub fn indi(&self) -> &I {
+        &self.indi
+    }
+
+    pub fn state_size(&self) -> usize {
+        self.state_size
+    }
+}
+
+impl<I> Individual for Recurrent<I>
+    where I: Individual {
+
+    /// Output of the first step of a sequence.
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
+        let mut state = self.reset_state();
+        self.step(input, &mut state)
+    }
+
+    fn mutate(&mut self, rng: &mut dyn RngCore) {
+        self.indi.mutate(rng);
+    }
+
+    fn crossover(&self, other: &Recurrent<I>, crossover: &Crossover, rng: &mut dyn RngCore) -> Recurrent<I> {
+        assert_eq!(self.state_size, other.state_size);
+        Recurrent {
+            indi: self.indi.crossover(&other.indi, crossover, rng),
+            state_size: self.state_size,
+        }
+    }
+
+    fn print(&self) {
+        println!("Recurrent (state size {})", self.state_size);
+        self.indi.print();
+    }
+
+    fn input_size(&self) -> usize {
+        self.indi.input_size() - self.state_size
+    }
+
+    fn output_size(&self) -> usize {
+        self.indi.output_size() - self.state_size
+    }
+
+    fn num_genes(&self) -> usize {
+        self.indi.num_genes()
+    }
+
+    fn mutate_gene(&mut self, gene: usize, rng: &mut dyn RngCore) {
+        self.indi.mutate_gene(gene, rng);
+    }
+
+    fn reset_state(&self) -> Vec<bool> {
+        vec![false; self.state_size]
+    }
+
+    fn step(&self, input: &[bool], state: &mut Vec<bool>) -> Vec<bool> {
+        assert_eq!(input.len(), self.input_size());
+        let mut full_input = input.to_vec();
+        full_input.extend_from_slice(state);
+        let mut output = self.indi.calculate_output(&full_input);
+        *state = output.split_off(self.output_size());
+        output
+    }
+}
+
+impl<I> Genome for Recurrent<I>
+    where I: Individual + Genome {
+
+    fn genes(&self) -> Vec<Gene> {
+        self.indi.genes()
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        self.indi.set_genes(genes);
+    }
+}
+
+impl<I> GenotypeDistance for Recurrent<I>
+    where I: Individual + GenotypeDistance {
+
+    fn genotype_distance(&self, other: &Recurrent<I>) -> f64 {
+        self.indi.genotype_distance(&other.indi)
+    }
+}
+
+impl<I> Checkpoint for Recurrent<I>
+    where I: Individual + Checkpoint {
+
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("Recurrent");
+        writer.write(self.state_size);
+        self.indi.save(writer);
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result< Recurrent<I> > {
+        reader.expect("Recurrent")?;
+        let state_size = reader.read()?;
+        let indi = I::load(reader)?;
+        Ok(Recurrent {
+            indi,
+            state_size,
+        })
+    }
+}
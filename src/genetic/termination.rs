@@ -0,0 +1,115 @@
+//! Conditions to stop learning a task, combined so that learning stops as soon as any of them holds.
+
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/// State of the learning process checked by the termination criteria.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Progress {
+    pub generation: usize,
+    pub num_evaluations: usize,
+    pub best_fitness: i32,
+    pub max_fitness: i32,
+    /// Whether the best individual solves the task (see Task::is_solved)
+    pub solved: bool,
+    /// Generations since the best fitness last improved (or since learning started)
+    pub stagnant_generations: usize,
+    /// Time since learning started
+    pub elapsed: Duration,
+}
+
+/// A condition to stop learning.
+pub enum Termination {
+    /// The best individual solves the task, by default reaching its max fitness (see Task::is_solved)
+    MaxFitness,
+    /// The best individual reaches at least the given fitness, which may be lower than the max fitness
+    TargetFitness(i32),
+    /// The population reaches the given generation
+    MaxGenerations(usize),
+    /// The task has calculated the given number of fitnesses in total
+    MaxEvaluations(usize),
+    /// The given time has passed since learning started
+    TimeBudget(Duration),
+    /// The best fitness hasn't improved for the given number of generations
+    Stagnation(usize),
+    /// A user supplied condition
+    Predicate(Box<dyn Fn(&Progress) -> bool>),
+}
+
+/// Why learning stopped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TerminationReason {
+    MaxFitness,
+    TargetFitness,
+    MaxGenerations,
+    MaxEvaluations,
+    TimeBudget,
+    Stagnation,
+    Predicate,
+}
+
+impl Termination {
+    /// Returns the reason to stop if the condition holds.
+    pub fn check(&self, progress: &Progress) -> Option<TerminationReason> {
+        let (holds, reason) = match *self {
+            Termination::MaxFitness => (progress.solved, TerminationReason::MaxFitness),
+            Termination::TargetFitness(target) => (progress.best_fitness >= target, TerminationReason::TargetFitness),
+            Termination::MaxGenerations(max) => (progress.generation >= max, TerminationReason::MaxGenerations),
+            Termination::MaxEvaluations(max) => (progress.num_evaluations >= max, TerminationReason::MaxEvaluations),
+            Termination::TimeBudget(budget) => (progress.elapsed >= budget, TerminationReason::TimeBudget),
+            Termination::Stagnation(max) => (progress.stagnant_generations >= max, TerminationReason::Stagnation),
+            Termination::Predicate(ref predicate) => (predicate(progress), TerminationReason::Predicate),
+        };
+        if holds {
+            Some(reason)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the reason of the first criterion that holds, if any.
+pub fn check_criteria(criteria: &[Termination], progress: &Progress) -> Option<TerminationReason> {
+    criteria.iter().filter_map(|criterion| criterion.check(progress)).next()
+}
+
+/// Keeps track of the time and the improvements since learning started.
+pub(crate) struct ProgressTracker {
+    start: Instant,
+    best_fitness: i32,
+    stagnant_generations: usize,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(best_fitness: i32) -> ProgressTracker {
+        ProgressTracker {
+            start: Instant::now(),
+            best_fitness,
+            stagnant_generations: 0,
+        }
+    }
+
+    /// To be called after each generation.
+    pub(crate) fn update(&mut self, best_fitness: i32) {
+        if best_fitness > self.best_fitness {
+            self.best_fitness = best_fitness;
+            self.stagnant_generations = 0;
+        } else {
+            self.stagnant_generations += 1;
+        }
+    }
+
+    pub(crate) fn progress(&self, generation: usize, num_evaluations: usize, best_fitness: i32, max_fitness: i32, solved: bool) -> Progress {
+        Progress {
+            generation,
+            num_evaluations,
+            best_fitness,
+            max_fitness,
+            solved,
+            stagnant_generations: self.stagnant_generations,
+            elapsed: self.start.elapsed(),
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Single trajectory optimizers, which move one individual through the search space instead of evolving a population.
+//! They can be used on the same individuals and tasks as Population::learn_task, to compare both approaches.
+
+use std::collections::VecDeque;
+
+use rand::{
+    Rng,
+    RngCore,
+    SeedableRng,
+};
+use rand_pcg::Pcg32;
+
+use crate::genetic::{
+    Individual,
+    RatedIndividual,
+    Task,
+};
+use crate::genetic::mutation::Mutation;
+use crate::genetic::termination::{
+    check_criteria,
+    ProgressTracker,
+    Termination,
+    TerminationReason,
+};
+
+fn clone_rated<I: Individual + Clone>(rated: &RatedIndividual<I>) -> RatedIndividual<I> {
+    RatedIndividual {
+        indi: rated.indi.clone(),
+        fitness: rated.fitness,
+    }
+}
+
+/// Strategy to move a single individual through the search space.
+pub trait LocalSearch<I: Individual> {
+    /// Returns the next current individual. Candidates must be rated with rate, which counts the evaluations.
+    fn step(&mut self, current: &RatedIndividual<I>, rate: &mut dyn FnMut(&I) -> i32, rng: &mut dyn RngCore) -> RatedIndividual<I>;
+}
+
+/// Creates new individuals, for example random ones to restart a search.
+pub type IndividualFactory<I> = Box<dyn Fn(&mut dyn RngCore) -> I>;
+
+/// Stochastic hill climbing: a mutated copy of the current individual replaces it unless it is worse.
+/// Optionally, it restarts from a new individual after some steps without improvement.
+pub struct HillClimbing<I: Individual> {
+    mutation: Mutation,
+    /// Steps without improvement before restarting, and the function creating the new starting individual
+    restart: Option<(usize, IndividualFactory<I>)>,
+    stagnant_steps: usize,
+}
+
+impl<I> HillClimbing<I>
+    where I: Individual {
+
+    pub fn new() -> HillClimbing<I> {
+        HillClimbing {
+            mutation: Mutation::Single,
+            restart: None,
+            stagnant_steps: 0,
+        }
+    }
+
+    pub fn set_mutation(&mut self, mutation: Mutation) {
+        assert!(mutation.is_valid(), "invalid mutation: {:?}", mutation);
+        self.mutation = mutation;
+    }
+
+    /// Restarts from an individual created by new_individual after max_stagnant_steps steps without improvement.
+    pub fn set_restarts<F>(&mut self, max_stagnant_steps: usize, new_individual: F)
+        where F: Fn(&mut dyn RngCore) -> I + 'static {
+        assert!(max_stagnant_steps > 0);
+        self.restart = Some((max_stagnant_steps, Box::new(new_individual)));
+    }
+}
+
+impl<I> Default for HillClimbing<I>
+    where I: Individual {
+
+    fn default() -> HillClimbing<I> {
+        HillClimbing::new()
+    }
+}
+
+impl<I> LocalSearch<I> for HillClimbing<I>
+    where I: Individual + Clone {
+
+    fn step(&mut self, current: &RatedIndividual<I>, rate: &mut dyn FnMut(&I) -> i32, rng: &mut dyn RngCore) -> RatedIndividual<I> {
+        if let Some((max_stagnant_steps, ref new_individual)) = self.restart {
+            if self.stagnant_steps >= max_stagnant_steps {
+                self.stagnant_steps = 0;
+                let indi = new_individual(rng);
+                let fitness = rate(&indi);
+                return RatedIndividual{indi, fitness};
+            }
+        }
+
+        let mut indi = current.indi.clone();
+        self.mutation.mutate(&mut indi, rng);
+        let fitness = rate(&indi);
+        if fitness > current.fitness {
+            self.stagnant_steps = 0;
+        } else {
+            self.stagnant_steps += 1;
+        }
+        // Equal fitness is accepted to drift along plateaus
+        if fitness >= current.fitness {
+            RatedIndividual{indi, fitness}
+        } else {
+            clone_rated(current)
+        }
+    }
+}
+
+/// How the temperature of simulated annealing decreases with the number of steps.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Cooling {
+    /// initial * alpha^step, with alpha between 0 and 1
+    Exponential { initial: f64, alpha: f64 },
+    /// initial - decrement * step, never below zero
+    Linear { initial: f64, decrement: f64 },
+    /// initial / ln(step + 2)
+    Logarithmic { initial: f64 },
+}
+
+impl Cooling {
+    pub fn temperature(&self, step: usize) -> f64 {
+        match *self {
+            Cooling::Exponential { initial, alpha } => initial * alpha.powi(step as i32),
+            Cooling::Linear { initial, decrement } => (initial - decrement * step as f64).max(0.0),
+            Cooling::Logarithmic { initial } => initial / ((step + 2) as f64).ln(),
+        }
+    }
+}
+
+/// Simulated annealing: a mutated copy of the current individual replaces it if it is not worse,
+/// and also if it is worse with probability exp(fitness difference / temperature).
+pub struct SimulatedAnnealing {
+    cooling: Cooling,
+    mutation: Mutation,
+    steps: usize,
+}
+
+impl SimulatedAnnealing {
+    pub fn new(cooling: Cooling) -> SimulatedAnnealing {
+        SimulatedAnnealing {
+            cooling,
+            mutation: Mutation::Single,
+            steps: 0,
+        }
+    }
+
+    pub fn set_mutation(&mut self, mutation: Mutation) {
+        assert!(mutation.is_valid(), "invalid mutation: {:?}", mutation);
+        self.mutation = mutation;
+    }
+
+    /// Temperature of the next step.
+    pub fn temperature(&self) -> f64 {
+        self.cooling.temperature(self.steps)
+    }
+}
+
+impl<I> LocalSearch<I> for SimulatedAnnealing
+    where I: Individual + Clone {
+
+    fn step(&mut self, current: &RatedIndividual<I>, rate: &mut dyn FnMut(&I) -> i32, rng: &mut dyn RngCore) -> RatedIndividual<I> {
+        let temperature = self.temperature();
+        self.steps += 1;
+
+        let mut indi = current.indi.clone();
+        self.mutation.mutate(&mut indi, rng);
+        let fitness = rate(&indi);
+        let accepted = if fitness >= current.fitness {
+            true
+        } else if temperature > 0.0 {
+            let probability = ((fitness - current.fitness) as f64 / temperature).exp();
+            rng.gen_bool(probability)
+        } else {
+            false
+        };
+        if accepted {
+            RatedIndividual{indi, fitness}
+        } else {
+            clone_rated(current)
+        }
+    }
+}
+
+/// Tabu search over single gene mutations: it always moves to the best neighbour, even if it is worse,
+/// but genes mutated recently are tabu unless mutating them again gives the best fitness found so far.
+pub struct TabuSearch {
+    /// Number of steps a mutated gene remains tabu
+    tenure: usize,
+    /// Number of random neighbours rated per step, all of them if None
+    num_neighbors: Option<usize>,
+    tabu: VecDeque<usize>,
+    best_fitness: Option<i32>,
+}
+
+impl TabuSearch {
+    pub fn new(tenure: usize) -> TabuSearch {
+        TabuSearch {
+            tenure,
+            num_neighbors: None,
+            tabu: VecDeque::with_capacity(tenure + 1),
+            best_fitness: None,
+        }
+    }
+
+    /// Rates only num_neighbors random genes per step, for genomes too big to rate all their neighbours.
+    pub fn set_num_neighbors(&mut self, num_neighbors: usize) {
+        assert!(num_neighbors > 0);
+        self.num_neighbors = Some(num_neighbors);
+    }
+}
+
+impl<I> LocalSearch<I> for TabuSearch
+    where I: Individual + Clone {
+
+    fn step(&mut self, current: &RatedIndividual<I>, rate: &mut dyn FnMut(&I) -> i32, rng: &mut dyn RngCore) -> RatedIndividual<I> {
+        let best_fitness = *self.best_fitness.get_or_insert(current.fitness);
+        let num_genes = current.indi.num_genes();
+        let genes : Vec<usize> = match self.num_neighbors {
+            None => (0..num_genes).collect(),
+            Some(num_neighbors) => (0..num_neighbors).map(|_| rng.gen_range(0, num_genes)).collect(),
+        };
+
+        let mut next : Option<(usize, RatedIndividual<I>)> = None;
+        for gene in genes {
+            let mut indi = current.indi.clone();
+            indi.mutate_gene(gene, rng);
+            let fitness = rate(&indi);
+            let aspiration = fitness > best_fitness;
+            if self.tabu.contains(&gene) && !aspiration {
+                continue;
+            }
+            let is_better = match next {
+                Some((_, ref rated)) => fitness > rated.fitness,
+                None => true,
+            };
+            if is_better {
+                next = Some((gene, RatedIndividual{indi, fitness}));
+            }
+        }
+
+        match next {
+            Some((gene, rated)) => {
+                self.tabu.push_back(gene);
+                if self.tabu.len() > self.tenure {
+                    self.tabu.pop_front();
+                }
+                if rated.fitness > best_fitness {
+                    self.best_fitness = Some(rated.fitness);
+                }
+                rated
+            },
+            // Every neighbour is tabu
+            None => clone_rated(current),
+        }
+    }
+}
+
+/// A single individual moved through the search space by a local search strategy, remembering the best one found.
+/// Each step counts as a generation for the termination criteria.
+///
+/// # Examples
+///
+/// ```
+/// use tetani::digital::BinOp;
+/// use tetani::digital::BinaryIndividual;
+/// use tetani::digital::TruthTable;
+/// use tetani::genetic::ImitationTask;
+/// use tetani::genetic::trajectory::Cooling;
+/// use tetani::genetic::trajectory::SimulatedAnnealing;
+/// use tetani::genetic::trajectory::Trajectory;
+/// let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+/// let annealing = SimulatedAnnealing::new(Cooling::Exponential { initial: 2.0, alpha: 0.99 });
+/// let mut trajectory = Trajectory::new(imitate_bi_xor_2, TruthTable::new_null(2, 1), annealing);
+/// trajectory.learn_task(1000);
+/// println!("Best fitness {} after {} evaluations", trajectory.best_fitness(), trajectory.num_evaluations());
+/// ```
+pub struct Trajectory<I: Individual, T: Task> {
+    pub task: T,
+    search: Box<dyn LocalSearch<I>>,
+    current: RatedIndividual<I>,
+    best: RatedIndividual<I>,
+    /// Number of steps taken so far
+    generation: usize,
+    /// Number of times the task has calculated the fitness of an individual
+    num_evaluations: usize,
+    rng: Pcg32,
+}
+
+impl<I, T> Trajectory<I, T>
+    where I: Individual + Clone, T: Task {
+
+    /// Rates the initial individual, which becomes both the current and the best individual.
+    pub fn new<S: LocalSearch<I> + 'static>(task: T, initial: I, search: S) -> Trajectory<I, T> {
+        let fitness = task.calculate_fitness(&initial);
+        let current = RatedIndividual {
+            indi: initial,
+            fitness,
+        };
+        Trajectory {
+            task,
+            search: Box::new(search),
+            best: clone_rated(&current),
+            current,
+            generation: 0,
+            num_evaluations: 1,
+            rng: Pcg32::from_rng(rand::thread_rng()).unwrap(),
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Pcg32::seed_from_u64(seed);
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations
+    }
+
+    pub fn current(&self) -> &I {
+        &self.current.indi
+    }
+
+    pub fn current_fitness(&self) -> i32 {
+        self.current.fitness
+    }
+
+    pub fn best(&self) -> &I {
+        &self.best.indi
+    }
+
+    pub fn best_fitness(&self) -> i32 {
+        self.best.fitness
+    }
+
+    pub fn step(&mut self) {
+        if self.task.prepare_generation(&mut self.rng) {
+            self.current.fitness = self.task.calculate_fitness(&self.current.indi);
+            self.best.fitness = self.task.calculate_fitness(&self.best.indi);
+            self.num_evaluations += 2;
+        }
+        let task = &self.task;
+        let num_evaluations = &mut self.num_evaluations;
+        let mut rate = |indi: &I| {
+            *num_evaluations += 1;
+            task.calculate_fitness(indi)
+        };
+        self.current = self.search.step(&self.current, &mut rate, &mut self.rng);
+        if self.current.fitness > self.best.fitness {
+            self.best = clone_rated(&self.current);
+        }
+        self.generation += 1;
+    }
+
+    /// Learns until reaching the max fitness of the task or the given number of steps.
+    pub fn learn_task(&mut self, max_generation: usize) -> TerminationReason {
+        self.learn_task_until(&[Termination::MaxFitness, Termination::MaxGenerations(max_generation)])
+    }
+
+    /// Learns until any of the criteria holds for the best individual found, see Population::learn_task_until.
+    pub fn learn_task_until(&mut self, criteria: &[Termination]) -> TerminationReason {
+        assert!(!criteria.is_empty());
+        let mut tracker = ProgressTracker::new(self.best_fitness());
+        loop {
+            let progress = tracker.progress(self.generation, self.num_evaluations, self.best_fitness(), self.task.max_fitness(), self.task.is_solved(self.best_fitness()));
+            if let Some(reason) = check_criteria(criteria, &progress) {
+                return reason;
+            }
+            self.step();
+            tracker.update(self.best_fitness());
+        }
+    }
+}
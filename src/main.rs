@@ -34,9 +34,9 @@ fn main() {
     let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task.clone(), population_size);
 
     pop_tt.add_unrated_individual(TruthTable::new_null(input_len, vector_size));
-    // pop_tt.add_unrated_individual(TruthTable::new_muta(input_len, vector_size, vector_size as u32 * 2));
-    // pop_tt.add_unrated_individual(TruthTable::new_muta(input_len, vector_size, vector_size as u32 * 4));
-    // pop_tt.add_unrated_individual(TruthTable::new_muta(input_len, vector_size, vector_size as u32 * 8));
+    // pop_tt.add_unrated_individual(TruthTable::new_muta(input_len, vector_size, vector_size as u32 * 2, &mut rng));
+    // pop_tt.add_unrated_individual(TruthTable::new_muta(input_len, vector_size, vector_size as u32 * 4, &mut rng));
+    // pop_tt.add_unrated_individual(TruthTable::new_muta(input_len, vector_size, vector_size as u32 * 8, &mut rng));
     pop_tt.rate_unrated_individuals();
 
     println!("Initial Truth Table population:");
@@ -46,7 +46,7 @@ fn main() {
 
     pop_tt.learn_task(max_generation);
     // pop_tt.print();
-    println!("Truth Table total generations: {}", pop_tt.len());
+    println!("Truth Table total generations: {}", pop_tt.generation());
     println!("Best Truth Table:");
     pop_tt.best().print();
 
@@ -54,9 +54,9 @@ fn main() {
     let mut pop_pla : Population<ProgrammableLogicArray, ImitationTask<BinaryIndividual>> = Population::new(bin_task, population_size);
 
     pop_pla.add_unrated_individual(ProgrammableLogicArray::new_null(input_len, vector_size));
-    // pop_pla.add_unrated_individual(ProgrammableLogicArray::new_mutated(input_len, vector_size, vector_size as u32 * 2));
-    // pop_pla.add_unrated_individual(ProgrammableLogicArray::new_mutated(input_len, vector_size, vector_size as u32 * 4));
-    // pop_pla.add_unrated_individual(ProgrammableLogicArray::new_mutated(input_len, vector_size, vector_size as u32 * 8));
+    // pop_pla.add_unrated_individual(ProgrammableLogicArray::new_mutated(input_len, vector_size, vector_size as u32 * 2, &mut rng));
+    // pop_pla.add_unrated_individual(ProgrammableLogicArray::new_mutated(input_len, vector_size, vector_size as u32 * 4, &mut rng));
+    // pop_pla.add_unrated_individual(ProgrammableLogicArray::new_mutated(input_len, vector_size, vector_size as u32 * 8, &mut rng));
     pop_pla.rate_unrated_individuals();
 
     println!("Initial Programable Logic Array population:");
@@ -66,7 +66,7 @@ fn main() {
 
     pop_pla.learn_task(max_generation);
     // pop_pla.print();
-    println!("Programable Logic Array total generations: {}", pop_pla.len());
+    println!("Programable Logic Array total generations: {}", pop_pla.generation());
     println!("Best Programable Logic Array:");
     pop_pla.best().print();
 }
@@ -1,10 +1,30 @@
 //! The neural module implements Evolutionary Artificial Neural Networks (EANNs).
 
-use rand::Rng;
+use std::convert::TryFrom;
+use std::io;
+
+use rand::{
+    Rng,
+    RngCore,
+};
 
 pub use crate::genetic::Individual;
+use crate::genetic::Crossover;
+use crate::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use crate::genetic::genome::{
+    genome_crossover,
+    genome_distance,
+    Gene,
+    Genome,
+};
+use crate::genetic::multiobjective::Complexity;
+use crate::genetic::niching::GenotypeDistance;
 
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash, Debug)]
 struct Layer {
     pub in_size: usize,
     weights: Vec<Vec<i8>>,
@@ -31,28 +51,50 @@ impl Layer {
         lay
     }
 
-    fn mutate(&mut self) {
-        let chosen_output = rand::thread_rng().gen_range(0, self.output_size());
-        let chosen_input = rand::thread_rng().gen_range(0, self.in_size + 1);
+    fn mutate(&mut self, rng: &mut dyn RngCore) {
+        let chosen_output = rng.gen_range(0, self.output_size());
+        let chosen_input = rng.gen_range(0, self.in_size + 1);
+        self.mutate_connection(chosen_output, chosen_input, rng);
+    }
+
+    /// Each weight and threshold is a gene, thresholds being the last gene of each neuron.
+    fn num_genes(&self) -> usize {
+        self.output_size() * (self.in_size + 1)
+    }
+
+    fn mutate_gene(&mut self, gene: usize, rng: &mut dyn RngCore) {
+        self.mutate_connection(gene / (self.in_size + 1), gene % (self.in_size + 1), rng);
+    }
+
+    /// Mutates the weight of the chosen input of the chosen output, or its threshold if chosen_input is in_size.
+    fn mutate_connection(&mut self, chosen_output: usize, chosen_input: usize, rng: &mut dyn RngCore) {
         let max_mutation = 4;
-        let mut mutation = rand::thread_rng().gen_range(1, max_mutation + 1);
+        let mut mutation = rng.gen_range(1, max_mutation + 1);
         // Half the times, make the connection weaker rather than stronger
-        if rand::thread_rng().gen_range(0, 2) > 0 {
+        if rng.gen_range(0, 2) > 0 {
             mutation = -mutation;
         }
         // Also mutate threshols some times
         if chosen_input == self.in_size {
-            self.thresholds[chosen_output] += mutation;
+            self.thresholds[chosen_output] = self.thresholds[chosen_output].saturating_add(mutation);
         } else {
-            self.weights[chosen_output][chosen_input] += mutation;
+            self.weights[chosen_output][chosen_input] = self.weights[chosen_output][chosen_input].saturating_add(mutation);
         }
     }
 
+    /// The genes of both layers (see Genome) are recombined by genome_crossover, so cut points may fall within a neuron.
+    fn crossover(&self, other: &Layer, crossover: &Crossover, rng: &mut dyn RngCore) -> Layer {
+        assert_eq!(self.in_size, other.in_size);
+        assert_eq!(self.output_size(), other.output_size());
+        genome_crossover(self, other, crossover, rng)
+    }
+
     fn output_size(&self) -> usize {
         self.thresholds.len()
     }
 
-    fn calculate_output(&self, input: &Vec<bool>) -> Vec<bool> {
+    #[allow(clippy::needless_range_loop)]
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
         assert_eq!(self.in_size, input.len());
         let mut output : Vec<bool> = Vec::with_capacity(self.output_size());
 
@@ -81,8 +123,60 @@ impl Clone for Layer {
     }
 }
 
+impl Genome for Layer {
+    /// Integer genes, each neuron being its weights followed by its threshold, like mutate_gene numbers them.
+    fn genes(&self) -> Vec<Gene> {
+        let mut genes = Vec::with_capacity(self.num_genes());
+        for i in 0..self.output_size() {
+            genes.extend(self.weights[i].iter().map(|&weight| Gene::Integer(weight as i32)));
+            genes.push(Gene::Integer(self.thresholds[i] as i32));
+        }
+        genes
+    }
+
+    /// Panics if a gene doesn't fit in the weights.
+    fn set_genes(&mut self, genes: &[Gene]) {
+        assert_eq!(self.num_genes(), genes.len());
+        let to_weight = |gene: &Gene| i8::try_from(gene.integer()).expect("weight out of range");
+        for i in 0..self.output_size() {
+            let neuron = &genes[i * (self.in_size + 1)..(i + 1) * (self.in_size + 1)];
+            for (weight, gene) in self.weights[i].iter_mut().zip(neuron.iter()) {
+                *weight = to_weight(gene);
+            }
+            self.thresholds[i] = to_weight(&neuron[self.in_size]);
+        }
+    }
+}
+
+impl Checkpoint for Layer {
+    /// Each neuron is written as its threshold followed by its weights.
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write(self.in_size);
+        writer.write(self.thresholds.len());
+        for i in 0..self.thresholds.len() {
+            writer.write(self.thresholds[i]);
+            for &weight in self.weights[i].iter() {
+                writer.write(weight);
+            }
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<Layer> {
+        let in_size : usize = reader.read()?;
+        let out_size : usize = reader.read()?;
+        let mut lay = Layer::new(in_size, out_size);
+        for i in 0..out_size {
+            lay.thresholds[i] = reader.read()?;
+            for j in 0..in_size {
+                lay.weights[i][j] = reader.read()?;
+            }
+        }
+        Ok(lay)
+    }
+}
+
 /// Artificial Neural Network
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Hash, Debug)]
 pub struct NeuralNetwork {
     layers: Vec<Layer>,
 }
@@ -102,7 +196,7 @@ impl NeuralNetwork {
         nn
     }
 
-    pub fn new_muta(in_size: usize, out_size: usize, net_depth: usize, num_mutations: u32) -> NeuralNetwork {
+    pub fn new_muta(in_size: usize, out_size: usize, net_depth: usize, num_mutations: u32, rng: &mut dyn RngCore) -> NeuralNetwork {
         let mut nn = NeuralNetwork {
             layers: vec![],
         };
@@ -113,7 +207,7 @@ impl NeuralNetwork {
         nn.layers.push(Layer::new(in_size, out_size));
 
         for _ in 0..num_mutations {
-            nn.mutate();
+            nn.mutate(rng);
         }
 
         nn
@@ -134,18 +228,47 @@ impl Individual for NeuralNetwork {
         println!("NeuralNetwork (net_depth {})", self.layers.len());
     }
 
-    fn mutate(&mut self) {
-        let chosen_layer = rand::thread_rng().gen_range(0, self.layers.len());
-        self.layers[chosen_layer].mutate();
+    fn mutate(&mut self, rng: &mut dyn RngCore) {
+        let chosen_layer = rng.gen_range(0, self.layers.len());
+        self.layers[chosen_layer].mutate(rng);
+    }
+
+    /// The genes of all layers, first layer first.
+    fn num_genes(&self) -> usize {
+        self.layers.iter().map(|layer| layer.num_genes()).sum()
+    }
+
+    fn mutate_gene(&mut self, gene: usize, rng: &mut dyn RngCore) {
+        let mut gene = gene;
+        for layer in self.layers.iter_mut() {
+            if gene < layer.num_genes() {
+                layer.mutate_gene(gene, rng);
+                return;
+            }
+            gene -= layer.num_genes();
+        }
+        panic!("gene out of range");
+    }
+
+    /// Each layer is recombined with the corresponding layer of the other network.
+    fn crossover(&self, other: &NeuralNetwork, crossover: &Crossover, rng: &mut dyn RngCore) -> NeuralNetwork {
+        assert_eq!(self.layers.len(), other.layers.len());
+        let mut nn = NeuralNetwork {
+            layers: Vec::with_capacity(self.layers.len()),
+        };
+        for i in 0..self.layers.len() {
+            nn.layers.push(self.layers[i].crossover(&other.layers[i], crossover, rng));
+        }
+        nn
     }
 
-    fn calculate_output(&self, input: &Vec<bool>) -> Vec<bool> {
+    fn calculate_output(&self, input: &[bool]) -> Vec<bool> {
         let num_layers = self.layers.len();
         assert!(num_layers > 0);
         assert_eq!(self.layers[0].in_size, input.len());
         let mut outputs : Vec<Vec<bool>> = Vec::with_capacity(num_layers);
 
-        outputs.push(self.layers[0].calculate_output(&input));
+        outputs.push(self.layers[0].calculate_output(input));
         for i in 0..num_layers - 1 {
             assert_eq!(self.layers[i+1].in_size, outputs[i].len());
             outputs.push(self.layers[i+1].calculate_output(&outputs[i]));
@@ -155,12 +278,75 @@ impl Individual for NeuralNetwork {
     }
 
     fn input_size(&self) -> usize {
-        assert!(self.layers.len() > 0);
+        assert!(!self.layers.is_empty());
         self.layers[0].in_size
     }
 
     fn output_size(&self) -> usize {
-        assert!(self.layers.len() > 0);
+        assert!(!self.layers.is_empty());
         self.layers[self.layers.len() - 1].thresholds.len()
     }
 }
+
+impl Checkpoint for NeuralNetwork {
+    fn save(&self, writer: &mut CheckpointWriter) {
+        writer.write("NeuralNetwork");
+        writer.write(self.layers.len());
+        writer.new_line();
+        for layer in self.layers.iter() {
+            layer.save(writer);
+            writer.new_line();
+        }
+    }
+
+    fn load(reader: &mut CheckpointReader) -> io::Result<NeuralNetwork> {
+        reader.expect("NeuralNetwork")?;
+        let net_depth : usize = reader.read()?;
+        let mut nn = NeuralNetwork {
+            layers: Vec::with_capacity(net_depth),
+        };
+        for _ in 0..net_depth {
+            nn.layers.push(Layer::load(reader)?);
+        }
+        Ok(nn)
+    }
+}
+
+impl Genome for NeuralNetwork {
+    /// The genes of all layers, first layer first.
+    fn genes(&self) -> Vec<Gene> {
+        self.layers.iter().flat_map(|layer| layer.genes()).collect()
+    }
+
+    fn set_genes(&mut self, genes: &[Gene]) {
+        assert_eq!(self.num_genes(), genes.len());
+        let mut first_gene = 0;
+        for layer in self.layers.iter_mut() {
+            let num_genes = layer.num_genes();
+            layer.set_genes(&genes[first_gene..first_gene + num_genes]);
+            first_gene += num_genes;
+        }
+    }
+}
+
+impl GenotypeDistance for NeuralNetwork {
+    /// Sum of the absolute differences between corresponding weights and thresholds.
+    fn genotype_distance(&self, other: &NeuralNetwork) -> f64 {
+        assert_eq!(self.layers.len(), other.layers.len());
+        genome_distance(self, other)
+    }
+}
+
+impl Complexity for NeuralNetwork {
+    /// Number of non zero weights and thresholds, the lower the sparser the network.
+    fn complexity(&self) -> usize {
+        let mut num_connections = 0;
+        for layer in self.layers.iter() {
+            for weights in layer.weights.iter() {
+                num_connections += weights.iter().filter(|&&weight| weight != 0).count();
+            }
+            num_connections += layer.thresholds.iter().filter(|&&threshold| threshold != 0).count();
+        }
+        num_connections
+    }
+}
@@ -0,0 +1,175 @@
+//! Checkpoint and resume
+//! A population restored from a checkpoint continues exactly like the population that was saved
+
+mod common;
+
+use std::path::PathBuf;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    Crossover,
+    ImitationTask,
+    Population,
+    Replacement,
+};
+use tetani::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use tetani::genetic::mutation::SelfAdaptive;
+use tetani::genetic::selection::TournamentSelection;
+use tetani::neural::NeuralNetwork;
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tetani_test_{}_{}.txt", name, std::process::id()))
+}
+
+fn round_trip<C: Checkpoint>(original: &C) -> C {
+    let mut writer = CheckpointWriter::new();
+    original.save(&mut writer);
+    let text = writer.into_string();
+    C::load(&mut CheckpointReader::new(&text)).unwrap()
+}
+
+fn new_pop_tt(seed: u64) -> Population<TruthTable, ImitationTask<TruthTable>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let task = common::imitate_rand_tt(4, 2, &mut rng);
+    let indis = (0..8).map(|_| TruthTable::new_rand(4, 2, &mut rng)).collect();
+
+    let mut pop_tt = common::new_pop_tt(task, indis);
+    pop_tt.set_seed(seed);
+    pop_tt.enable_fitness_cache();
+    pop_tt.set_replacement(Replacement::Generational { elites: 2 });
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(16);
+    pop_tt.set_crossover_rate(0.5);
+    pop_tt.set_crossover(Crossover::TwoPoint);
+    pop_tt
+}
+
+fn tt_resumes_from_checkpoint(seed: u64) {
+    let path = checkpoint_path(&format!("tt_{}", seed));
+    let mut pop_tt = new_pop_tt(seed);
+    pop_tt.set_selection(TournamentSelection::new(3, 0.9));
+    for _ in 0..20 {
+        pop_tt.next_generation();
+    }
+    pop_tt.save_checkpoint(&path).unwrap();
+
+    let mut resumed : Population<TruthTable, ImitationTask<TruthTable>> = Population::load_checkpoint(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    resumed.set_selection(TournamentSelection::new(3, 0.9));
+    assert_eq!(pop_tt.generation(), resumed.generation());
+    assert_eq!(pop_tt.num_evaluations(), resumed.num_evaluations());
+    assert_eq!(pop_tt.best(), resumed.best());
+    assert_eq!(pop_tt.stats(), resumed.stats());
+
+    for _ in 0..50 {
+        pop_tt.next_generation();
+        resumed.next_generation();
+        assert_eq!(pop_tt.stats(), resumed.stats());
+    }
+    assert_eq!(pop_tt.best(), resumed.best());
+    assert_eq!(pop_tt.cache_hits(), resumed.cache_hits());
+    assert_eq!(pop_tt.cache_misses(), resumed.cache_misses());
+}
+
+#[test]
+fn tt_resumes_from_checkpoint_exactly() {
+    for seed in 0..10 {
+        tt_resumes_from_checkpoint(seed);
+    }
+}
+
+fn tt_run_ignores_checkpoint(seed: u64) {
+    let mut uninterrupted = new_pop_tt(seed);
+    uninterrupted.set_selection(TournamentSelection::new(3, 0.9));
+    let mut checkpointed = new_pop_tt(seed);
+    checkpointed.set_selection(TournamentSelection::new(3, 0.9));
+    for _ in 0..20 {
+        uninterrupted.next_generation();
+        checkpointed.next_generation();
+    }
+    let mut writer = CheckpointWriter::new();
+    checkpointed.write_checkpoint(&mut writer);
+
+    for _ in 0..50 {
+        uninterrupted.next_generation();
+        checkpointed.next_generation();
+        assert_eq!(uninterrupted.stats(), checkpointed.stats());
+    }
+    assert_eq!(uninterrupted.best(), checkpointed.best());
+}
+
+#[test]
+fn tt_run_ignores_checkpoint_exactly() {
+    for seed in 0..10 {
+        tt_run_ignores_checkpoint(seed);
+    }
+}
+
+#[test]
+fn individuals_round_trip() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..10 {
+        let tt = TruthTable::new_rand(3, 2, &mut rng);
+        assert_eq!(tt, round_trip(&tt));
+        let pla = ProgrammableLogicArray::new_rand(2, 3, &mut rng);
+        assert_eq!(pla, round_trip(&pla));
+        let nn = NeuralNetwork::new_muta(4, 2, 3, 20, &mut rng);
+        assert_eq!(nn, round_trip(&nn));
+    }
+    let bi = BinaryIndividual::new(BinOp::NAND, 6);
+    assert_eq!(bi, round_trip(&bi));
+    let tt_empt = TruthTable::new_empt(2);
+    assert_eq!(tt_empt, round_trip(&tt_empt));
+}
+
+#[test]
+fn invalid_checkpoints_fail_to_load() {
+    let path = checkpoint_path("invalid");
+    let pop_tt = new_pop_tt(0);
+    pop_tt.save_checkpoint(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let truncated = &text[..text.len() / 2];
+    assert!(Population::<TruthTable, ImitationTask<TruthTable>>::read_checkpoint(&mut CheckpointReader::new(truncated)).is_err());
+    // A checkpoint of truth tables can't be loaded as neural networks
+    assert!(Population::<NeuralNetwork, ImitationTask<TruthTable>>::read_checkpoint(&mut CheckpointReader::new(&text)).is_err());
+    assert!(Population::<TruthTable, ImitationTask<TruthTable>>::load_checkpoint(checkpoint_path("missing")).is_err());
+}
+
+#[test]
+fn out_of_range_values_fail_to_load() {
+    let path = checkpoint_path("out_of_range");
+    let pop_tt = new_pop_tt(0);
+    pop_tt.save_checkpoint(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for (valid, invalid) in [("crossover_rate 0.5 ", "crossover_rate 1.5 "), ("mutation Single ", "mutation PerGene -0.1 "),
+                             ("num_selected 4 ", "num_selected 0 "), ("rng 16 ", "rng 2 ")].iter() {
+        assert!(text.contains(valid));
+        let corrupted = text.replace(valid, invalid);
+        let error = Population::<TruthTable, ImitationTask<TruthTable>>::read_checkpoint(&mut CheckpointReader::new(&corrupted)).err().unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+    }
+    let mut writer = CheckpointWriter::new();
+    SelfAdaptive::new(TruthTable::new_null(2, 1), 0.5).save(&mut writer);
+    let text = writer.into_string();
+    for invalid in ["SelfAdaptive 0 ", "SelfAdaptive 1.5 "].iter() {
+        let corrupted = text.replace("SelfAdaptive 0.5 ", invalid);
+        let error = SelfAdaptive::<TruthTable>::load(&mut CheckpointReader::new(&corrupted)).err().unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+    }
+}
@@ -0,0 +1,203 @@
+//! Competitive co-evolution
+//! Individuals rated by games against each other improve through arms races, without a fixed goal
+
+use std::hash::Hash;
+
+use tetani::digital::{
+    Individual,
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    Population,
+    Task,
+};
+use tetani::genetic::coevolution::{
+    Coevolution,
+    CoevolutionTask,
+    Game,
+    DEFAULT_MAX_CHAMPIONS,
+    Opponents,
+    Role,
+    SelfPlay,
+};
+
+const OUT_SIZE : usize = 4;
+
+/// The player with more ones in its output wins, a tie scores nothing.
+#[derive(Clone)]
+struct MoreOnes;
+
+fn count_ones(indi: &dyn Individual) -> usize {
+    indi.calculate_output(&[false]).iter().filter(|&&bit| bit).count()
+}
+
+impl Game for MoreOnes {
+    fn play(&self, first: &dyn Individual, second: &dyn Individual) -> (i32, i32) {
+        let first_ones = count_ones(first);
+        let second_ones = count_ones(second);
+        if first_ones > second_ones {
+            (1, 0)
+        } else if second_ones > first_ones {
+            (0, 1)
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn max_score(&self) -> i32 {
+        1
+    }
+}
+
+/// The first player scores the number of its ones, the second one the number of its zeros.
+struct OnesAgainstZeros;
+
+impl Game for OnesAgainstZeros {
+    fn play(&self, first: &dyn Individual, second: &dyn Individual) -> (i32, i32) {
+        (count_ones(first) as i32, (OUT_SIZE - count_ones(second)) as i32)
+    }
+
+    fn max_score(&self) -> i32 {
+        OUT_SIZE as i32
+    }
+}
+
+fn new_population<I, O>(indi: I, role: Role) -> Population<I, CoevolutionTask<O, MoreOnes>>
+    where I: Individual + 'static + Clone + Send + Sync + Hash, O: Individual + Sync {
+
+    let mut pop = Population::new(CoevolutionTask::new(MoreOnes, role), 16);
+    for _ in 0..4 {
+        pop.add_unrated_individual(indi.clone());
+    }
+    pop.rate_unrated_individuals();
+    pop.set_num_selected(4);
+    pop.set_num_offspring(8);
+    pop
+}
+
+fn self_play_tt_gets_all_ones(opponents: Opponents, num_generations: usize) {
+    let mut self_play = SelfPlay::new(new_population::<TruthTable, TruthTable>(TruthTable::new_null(1, OUT_SIZE), Role::First));
+    self_play.set_opponents(opponents);
+    self_play.evolve(num_generations);
+    assert_eq!(num_generations.min(DEFAULT_MAX_CHAMPIONS), self_play.champions().len());
+    assert_eq!(OUT_SIZE, count_ones(self_play.champions().last().unwrap()));
+}
+
+fn tt_and_pla_get_all_ones(opponents: Opponents, num_generations: usize) {
+    let hosts = new_population(TruthTable::new_null(1, OUT_SIZE), Role::First);
+    let parasites = new_population(ProgrammableLogicArray::new_null(1, OUT_SIZE), Role::Second);
+    let mut coevolution = Coevolution::new(hosts, parasites);
+    coevolution.set_opponents(opponents);
+    coevolution.evolve(num_generations);
+    assert_eq!(num_generations, coevolution.generation());
+    assert_eq!(OUT_SIZE, count_ones(coevolution.host_champions().last().unwrap()));
+    assert_eq!(OUT_SIZE, count_ones(coevolution.parasite_champions().last().unwrap()));
+}
+
+#[test]
+fn self_play_round_robin() {
+    for _ in 0..10 {
+        self_play_tt_gets_all_ones(Opponents::RoundRobin, 200);
+    }
+}
+
+#[test]
+fn self_play_random_opponents() {
+    for _ in 0..10 {
+        self_play_tt_gets_all_ones(Opponents::Random(4), 200);
+    }
+}
+
+#[test]
+fn self_play_champions() {
+    for _ in 0..10 {
+        self_play_tt_gets_all_ones(Opponents::Champions(4), 200);
+    }
+}
+
+#[test]
+fn hosts_and_parasites_round_robin() {
+    for _ in 0..10 {
+        tt_and_pla_get_all_ones(Opponents::RoundRobin, 300);
+    }
+}
+
+#[test]
+fn hosts_and_parasites_champions() {
+    for _ in 0..10 {
+        tt_and_pla_get_all_ones(Opponents::Champions(4), 300);
+    }
+}
+
+#[test]
+fn opponents_are_set_each_generation() {
+    let hosts = new_population(TruthTable::new_null(1, OUT_SIZE), Role::First);
+    let parasites = new_population(TruthTable::new_null(1, OUT_SIZE), Role::Second);
+    let mut coevolution = Coevolution::new(hosts, parasites);
+    coevolution.set_seed(0);
+
+    coevolution.set_opponents(Opponents::Champions(3));
+    coevolution.next_generation();
+    // No champions yet, so only the best opponent
+    assert_eq!(1, coevolution.hosts().task.opponents().len());
+    coevolution.evolve(4);
+    assert_eq!(3, coevolution.hosts().task.opponents().len());
+    assert_eq!(5, coevolution.parasite_champions().len());
+
+    coevolution.set_opponents(Opponents::Random(3));
+    coevolution.next_generation();
+    assert_eq!(3, coevolution.parasites().task.opponents().len());
+    assert_eq!(3, coevolution.parasites().task.max_fitness());
+
+    coevolution.set_opponents(Opponents::RoundRobin);
+    coevolution.next_generation();
+    assert_eq!(coevolution.parasites().individuals().len(), coevolution.hosts().task.opponents().len());
+}
+
+#[test]
+fn only_the_last_champions_are_kept() {
+    let mut self_play = SelfPlay::new(new_population::<TruthTable, TruthTable>(TruthTable::new_null(1, OUT_SIZE), Role::First));
+    self_play.set_max_champions(3);
+    self_play.set_opponents(Opponents::Champions(5));
+    self_play.evolve(10);
+    assert_eq!(3, self_play.champions().len());
+    assert_eq!(self_play.population().best(), self_play.champions().last().unwrap());
+    assert_eq!(3, self_play.population().task.opponents().len());
+
+    let hosts = new_population(TruthTable::new_null(1, OUT_SIZE), Role::First);
+    let parasites = new_population(TruthTable::new_null(1, OUT_SIZE), Role::Second);
+    let mut coevolution = Coevolution::new(hosts, parasites);
+    coevolution.evolve(5);
+    coevolution.set_max_champions(2);
+    assert_eq!(2, coevolution.host_champions().len());
+    assert_eq!(2, coevolution.parasite_champions().len());
+    assert_eq!(coevolution.hosts().best(), coevolution.host_champions().last().unwrap());
+}
+
+#[test]
+fn role_chooses_the_score() {
+    let zeros = TruthTable::new_null(1, OUT_SIZE);
+    let mut first_task = CoevolutionTask::new(OnesAgainstZeros, Role::First);
+    let mut second_task = CoevolutionTask::new(OnesAgainstZeros, Role::Second);
+    first_task.set_opponents(vec![zeros.clone(), zeros.clone()]);
+    second_task.set_opponents(vec![zeros.clone(), zeros.clone()]);
+    assert_eq!(2 * OUT_SIZE as i32, first_task.max_fitness());
+    assert_eq!(0, first_task.calculate_fitness(&zeros));
+    assert_eq!(vec![OUT_SIZE as i32, OUT_SIZE as i32], first_task.calculate_case_errors(&zeros));
+    assert_eq!(2 * OUT_SIZE as i32, second_task.calculate_fitness(&zeros));
+    assert_eq!(vec![0, 0], second_task.calculate_case_errors(&zeros));
+}
+
+#[test]
+fn self_play_archive() {
+    for _ in 0..10 {
+        let mut pop = new_population::<TruthTable, TruthTable>(TruthTable::new_null(1, OUT_SIZE), Role::First);
+        pop.enable_hall_of_fame(4);
+        let mut self_play = SelfPlay::new(pop);
+        self_play.set_opponents(Opponents::Archive(4));
+        self_play.evolve(200);
+        assert_eq!(OUT_SIZE, count_ones(self_play.champions().last().unwrap()));
+        assert!(self_play.population().task.opponents().len() <= 4);
+    }
+}
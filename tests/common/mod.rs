@@ -0,0 +1,37 @@
+//! Fixtures shared by the integration tests
+//! Each test file only uses some of them
+
+#![allow(dead_code)]
+
+use rand::RngCore;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+
+/// Task imitating a random truth table.
+pub fn imitate_rand_tt(in_size: usize, out_size: usize, rng: &mut dyn RngCore) -> ImitationTask<TruthTable> {
+    ImitationTask::new(TruthTable::new_rand(in_size, out_size, rng))
+}
+
+/// Task imitating the operation on two vectors of vector_size bits.
+pub fn imitate_bi(operation_type: BinOp, vector_size: usize) -> ImitationTask<BinaryIndividual> {
+    ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2))
+}
+
+/// Population of 16 truth tables learning the task, starting with the given individuals, already rated.
+pub fn new_pop_tt<T: Task + Sync>(task: T, indis: Vec<TruthTable>) -> Population<TruthTable, T> {
+    let mut pop_tt : Population<TruthTable, T> = Population::new(task, 16);
+    for indi in indis {
+        pop_tt.add_unrated_individual(indi);
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt
+}
@@ -0,0 +1,97 @@
+//! Recombining individuals with crossover
+//! Children only inherit genes from their parents and populations can still learn with crossover
+
+use tetani::digital::{
+    get_null_bitvector,
+    increment_bitvector,
+    BinOp,
+    BinaryIndividual,
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    Crossover,
+    ImitationTask,
+    Individual,
+    Population,
+    Task,
+};
+
+fn child_inherits_from_parents<I: Individual>(parent_a: &I, parent_b: &I, child: &I) {
+    let mut input = get_null_bitvector(child.input_size());
+    let input_space_cardinality = 2usize.pow(child.input_size() as u32);
+    for j in 0..input_space_cardinality {
+        let output_a = parent_a.calculate_output(&input);
+        let output_b = parent_b.calculate_output(&input);
+        let output_child = child.calculate_output(&input);
+        for i in 0..output_child.len() {
+            assert!(output_child[i] == output_a[i] || output_child[i] == output_b[i]);
+        }
+        if j < input_space_cardinality - 1 {
+            increment_bitvector(&mut input);
+        }
+    }
+}
+
+#[test]
+fn tt_crossover_inherits_from_parents() {
+    let mut rng = rand::thread_rng();
+    for crossover in [Crossover::OnePoint, Crossover::TwoPoint, Crossover::Uniform].iter() {
+        for _ in 0..1000 {
+            let parent_a = TruthTable::new_rand(3, 2, &mut rng);
+            let parent_b = TruthTable::new_rand(3, 2, &mut rng);
+            let child = parent_a.crossover(&parent_b, crossover, &mut rng);
+            child_inherits_from_parents(&parent_a, &parent_b, &child);
+        }
+    }
+}
+
+#[test]
+fn tt_crossover_same_parents() {
+    let mut rng = rand::thread_rng();
+    let parent = TruthTable::new_rand(3, 2, &mut rng);
+    let task = ImitationTask::new(parent.clone());
+    for crossover in [Crossover::OnePoint, Crossover::TwoPoint, Crossover::Uniform].iter() {
+        let child = parent.crossover(&parent, crossover, &mut rng);
+        assert_eq!(task.max_fitness(), task.calculate_fitness(&child));
+    }
+}
+
+#[test]
+fn pla_crossover_same_parents() {
+    let mut rng = rand::thread_rng();
+    let parent = ProgrammableLogicArray::new_rand(2, 2, &mut rng);
+    let task = ImitationTask::new(parent.clone());
+    for crossover in [Crossover::OnePoint, Crossover::TwoPoint, Crossover::Uniform].iter() {
+        let child = parent.crossover(&parent, crossover, &mut rng);
+        assert_eq!(task.max_fitness(), task.calculate_fitness(&child));
+    }
+}
+
+fn tt_can_learn_bi_with_crossover(operation_type: BinOp, vector_size: usize, crossover: Crossover, max_generation: usize) {
+    let bi = BinaryIndividual::new(operation_type, vector_size * 2);
+    let bin_task = ImitationTask::new(bi);
+    let max_fitness = bin_task.max_fitness();
+    let mut rng = rand::thread_rng();
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, max_generation);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_rand(vector_size * 2, vector_size, &mut rng));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_crossover_rate(0.5);
+    pop_tt.set_crossover(crossover);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_with_crossover() {
+    for _ in 0..10 {
+        tt_can_learn_bi_with_crossover(BinOp::XOR, 2, Crossover::OnePoint, 2000);
+        tt_can_learn_bi_with_crossover(BinOp::XOR, 2, Crossover::TwoPoint, 2000);
+        tt_can_learn_bi_with_crossover(BinOp::XOR, 2, Crossover::Uniform, 2000);
+    }
+}
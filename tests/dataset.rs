@@ -0,0 +1,130 @@
+//! Dataset tasks
+//! Individuals learn recorded examples, and are measured on examples kept out of training
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+use tetani::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use tetani::genetic::dataset::{
+    Dataset,
+    DatasetTask,
+};
+
+fn dataset_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("tetani_test_{}_{}.txt", name, std::process::id()))
+}
+
+fn tt_can_learn_dataset(dataset: &Dataset, max_generation: usize) {
+    let dataset_task = DatasetTask::from_split(dataset, 0.75, 0.0, 0);
+    let mut pop_tt : Population<TruthTable, DatasetTask> = Population::new(dataset_task, 16);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(dataset.input_size(), dataset.output_size()));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(8);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(pop_tt.task.train().max_fitness(), pop_tt.best_fitness());
+    assert!(pop_tt.task.test_fitness(pop_tt.best()) <= pop_tt.task.test().max_fitness());
+}
+
+#[test]
+fn tt_can_learn_bi_or2_dataset_file() {
+    let path = dataset_path("or2_dataset");
+    let mut text = String::from("# 2 bit OR\n");
+    for example in Dataset::from_individual(&BinaryIndividual::new(BinOp::OR, 4)).examples() {
+        let bits = |bits: &[bool]| bits.iter().map(|&bit| if bit { '1' } else { '0' }).collect::<String>();
+        text.push_str(&format!("{} {}\n", bits(&example.input), bits(&example.output)));
+    }
+    std::fs::write(&path, text).unwrap();
+    let dataset = Dataset::load_text(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(16, dataset.len());
+
+    for _ in 0..10 {
+        tt_can_learn_dataset(&dataset, 1000);
+    }
+}
+
+#[test]
+fn dataset_fitness_equals_imitation_fitness() {
+    let bi_xor_4 = BinaryIndividual::new(BinOp::XOR, 4);
+    let dataset_task = DatasetTask::new(Dataset::from_individual(&bi_xor_4), Dataset::new(4, 2), Dataset::new(4, 2));
+    let imitation_task = ImitationTask::new(bi_xor_4);
+    assert_eq!(imitation_task.max_fitness(), dataset_task.max_fitness());
+    let mut rng = StdRng::seed_from_u64(0);
+    for indi in [TruthTable::new_null(4, 2), TruthTable::new_rand(4, 2, &mut rng)].iter() {
+        assert_eq!(imitation_task.calculate_fitness(indi), dataset_task.calculate_fitness(indi));
+    }
+    assert_eq!(0, dataset_task.validation_fitness(&TruthTable::new_null(4, 2)));
+}
+
+#[test]
+fn csv_with_header() {
+    let text = "a,b,a_and_b\n0,0,0\n0,1,0\n1,0,0\ntrue,true,true\n";
+    let dataset = Dataset::parse_csv(text, 2).unwrap();
+    assert_eq!(4, dataset.len());
+    assert_eq!(2, dataset.input_size());
+    assert_eq!(1, dataset.output_size());
+    assert_eq!(vec![true, true], dataset.examples()[3].input);
+    assert_eq!(vec![true], dataset.examples()[3].output);
+}
+
+#[test]
+fn invalid_datasets_are_rejected() {
+    assert!(Dataset::parse_text("").is_err());
+    assert!(Dataset::parse_text("01 1\n011 1\n").is_err());
+    assert!(Dataset::parse_text("01 1\n0x 1\n").is_err());
+    assert!(Dataset::parse_text("01\n").is_err());
+    assert!(Dataset::parse_csv("0,1,1\n0,1,2\n", 2).is_err());
+    assert!(Dataset::parse_csv("0,1\n", 2).is_err());
+}
+
+#[test]
+fn split_is_a_seeded_partition() {
+    let dataset = Dataset::from_individual(&BinaryIndividual::new(BinOp::AND, 6));
+    let (train, validation, test) = dataset.split(0.5, 0.25, 3);
+    assert_eq!(32, train.len());
+    assert_eq!(16, validation.len());
+    assert_eq!(16, test.len());
+
+    let mut inputs : Vec< Vec<bool> > = train.examples().iter()
+        .chain(validation.examples().iter())
+        .chain(test.examples().iter())
+        .map(|example| example.input.clone())
+        .collect();
+    inputs.sort();
+    inputs.dedup();
+    assert_eq!(64, inputs.len());
+
+    assert_eq!((train.clone(), validation.clone(), test.clone()), dataset.split(0.5, 0.25, 3));
+    assert_ne!(train, dataset.split(0.5, 0.25, 4).0);
+}
+
+#[test]
+fn dataset_task_checkpoint() {
+    let dataset = Dataset::from_individual(&BinaryIndividual::new(BinOp::NAND, 4));
+    let dataset_task = DatasetTask::from_split(&dataset, 0.5, 0.25, 1);
+    let mut writer = CheckpointWriter::new();
+    dataset_task.save(&mut writer);
+    let text = writer.into_string();
+    let restored = DatasetTask::load(&mut CheckpointReader::new(&text)).unwrap();
+    assert_eq!(dataset_task.train(), restored.train());
+    assert_eq!(dataset_task.validation(), restored.validation());
+    assert_eq!(dataset_task.test(), restored.test());
+}
@@ -0,0 +1,171 @@
+//! Population diversity
+//! Diversity metrics detect converged populations, and diversity policies react to them
+
+mod common;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+use tetani::genetic::checkpoint::{
+    CheckpointReader,
+    CheckpointWriter,
+};
+use tetani::genetic::diversity::{
+    gene_entropies,
+    mean_pairwise_distance,
+    num_distinct_phenotypes,
+    Diversity,
+    DiversityMetric,
+    DiversityPolicy,
+    DiversityResponse,
+};
+use tetani::genetic::genome::{
+    Gene,
+    Genome,
+};
+use tetani::genetic::mutation::Mutation;
+
+/// Population of identical individuals, fully converged.
+fn new_converged_pop_tt(in_size: usize, out_size: usize) -> Population<TruthTable, ImitationTask<TruthTable>> {
+    let task = common::imitate_rand_tt(in_size, out_size, &mut rand::thread_rng());
+    let mut pop_tt = common::new_pop_tt(task, vec![TruthTable::new_null(in_size, out_size); 16]);
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(8);
+    pop_tt
+}
+
+fn tt_can_learn_tt_with_policy(response: DiversityResponse, max_generation: usize) {
+    let mut pop_tt = new_converged_pop_tt(4, 2);
+    let max_fitness = pop_tt.task.max_fitness();
+    pop_tt.set_diversity_policy(DiversityPolicy::new(DiversityMetric::MeanPairwiseDistance, 1.0, response));
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+}
+
+#[test]
+fn tt_can_learn_tt_in4_out2_with_restarts() {
+    for _ in 0..10 {
+        tt_can_learn_tt_with_policy(DiversityResponse::Restart { survivors: 4 }, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_tt_in4_out2_with_mutation_boosts() {
+    for _ in 0..10 {
+        tt_can_learn_tt_with_policy(DiversityResponse::MutationBoost { mutation: Mutation::Flips(3), generations: 5 }, 2000);
+    }
+}
+
+#[test]
+fn diversity_of_identical_and_complementary_individuals() {
+    let null = TruthTable::new_null(2, 1);
+    let mut ones = null.clone();
+    ones.set_genes(&[Gene::Bit(true); 4]);
+
+    let identical = [&null, &null, &null];
+    assert_eq!(Diversity { mean_pairwise_distance: 0.0, mean_gene_entropy: 0.0, num_distinct_phenotypes: 1 }, Diversity::new(&identical));
+
+    let complementary = [&null, &ones];
+    assert_eq!(Diversity { mean_pairwise_distance: 4.0, mean_gene_entropy: 1.0, num_distinct_phenotypes: 2 }, Diversity::new(&complementary));
+    assert_eq!(vec![1.0; 4], gene_entropies(&complementary));
+
+    // Three pairs at distances 0, 4 and 4
+    assert_eq!(8.0 / 3.0, mean_pairwise_distance(&[&null, &null, &ones]));
+    assert_eq!(0.0, mean_pairwise_distance(&[&null]));
+    assert_eq!(1, num_distinct_phenotypes(&[&BinaryIndividual::new(BinOp::OR, 2), &BinaryIndividual::new(BinOp::OR, 2)]));
+}
+
+#[test]
+fn restart_makes_a_converged_population_diverse() {
+    let mut pop_tt = new_converged_pop_tt(3, 2);
+    pop_tt.set_seed(0);
+    assert_eq!(0.0, pop_tt.diversity().mean_pairwise_distance);
+    pop_tt.set_diversity_policy(DiversityPolicy::new(DiversityMetric::DistinctPhenotypes, 10.0, DiversityResponse::Restart { survivors: 1 }));
+    pop_tt.next_generation();
+    assert_eq!(1, pop_tt.diversity_policy().unwrap().num_responses());
+    assert_eq!(16, pop_tt.len());
+    assert!(pop_tt.diversity().num_distinct_phenotypes >= 10);
+    assert!(pop_tt.diversity().mean_gene_entropy > 0.5);
+}
+
+#[test]
+fn mutation_boost_lasts_the_given_generations() {
+    let mut pop_tt = new_converged_pop_tt(3, 2);
+    let boost = DiversityResponse::MutationBoost { mutation: Mutation::PerGene(0.5), generations: 3 };
+    // Never diverse enough, so it boosts whenever it isn't boosting already
+    pop_tt.set_diversity_policy(DiversityPolicy::new(DiversityMetric::MeanGeneEntropy, 2.0, boost));
+    pop_tt.next_generation();
+    assert!(pop_tt.diversity_policy().unwrap().is_boosting());
+    assert_eq!(Mutation::PerGene(0.5), *pop_tt.mutation());
+    for _ in 0..3 {
+        pop_tt.next_generation();
+    }
+    assert!(!pop_tt.diversity_policy().unwrap().is_boosting());
+    assert_eq!(Mutation::Single, *pop_tt.mutation());
+    pop_tt.next_generation();
+    assert_eq!(2, pop_tt.diversity_policy().unwrap().num_responses());
+}
+
+#[test]
+fn mutation_boost_is_not_checkpointed() {
+    let mut pop_tt = new_converged_pop_tt(3, 2);
+    pop_tt.set_mutation(Mutation::Flips(2));
+    let boost = DiversityResponse::MutationBoost { mutation: Mutation::PerGene(0.5), generations: 3 };
+    pop_tt.set_diversity_policy(DiversityPolicy::new(DiversityMetric::MeanGeneEntropy, 2.0, boost));
+    pop_tt.next_generation();
+    assert!(pop_tt.diversity_policy().unwrap().is_boosting());
+    assert_eq!(Mutation::Flips(2), *pop_tt.unboosted_mutation());
+
+    // Checkpoints taken during a boost keep the mutation from before it
+    let mut writer = CheckpointWriter::new();
+    pop_tt.write_checkpoint(&mut writer);
+    let text = writer.into_string();
+    let mut resumed : Population<TruthTable, ImitationTask<TruthTable>> =
+        Population::read_checkpoint(&mut CheckpointReader::new(&text)).unwrap();
+    assert_eq!(Mutation::Flips(2), *resumed.mutation());
+    let boost = DiversityResponse::MutationBoost { mutation: Mutation::PerGene(0.5), generations: 3 };
+    resumed.set_diversity_policy(DiversityPolicy::new(DiversityMetric::MeanGeneEntropy, 2.0, boost));
+    for _ in 0..4 {
+        resumed.next_generation();
+    }
+    assert_eq!(Mutation::Flips(2), *resumed.mutation());
+    assert_eq!(pop_tt.generation() + 4, resumed.generation());
+
+    // Setting a mutation ends the boost
+    pop_tt.set_mutation(Mutation::Flips(1));
+    assert!(!pop_tt.diversity_policy().unwrap().is_boosting());
+    assert_eq!(Mutation::Flips(1), *pop_tt.mutation());
+    for _ in 0..4 {
+        pop_tt.next_generation();
+    }
+    assert_eq!(Mutation::Flips(1), *pop_tt.mutation());
+
+    // So does setting another policy
+    pop_tt.next_generation();
+    assert_eq!(Mutation::PerGene(0.5), *pop_tt.mutation());
+    pop_tt.set_diversity_policy(DiversityPolicy::new(DiversityMetric::MeanGeneEntropy, 0.0, DiversityResponse::Restart { survivors: 4 }));
+    assert_eq!(Mutation::Flips(1), *pop_tt.mutation());
+}
+
+#[test]
+fn diversity_of_a_random_population() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let indis : Vec<TruthTable> = (0..64).map(|_| TruthTable::new_rand(4, 1, &mut rng)).collect();
+    let refs : Vec<&TruthTable> = indis.iter().collect();
+    let diversity = Diversity::new(&refs);
+    // Random bits differ half the times
+    assert!((diversity.mean_pairwise_distance - 8.0).abs() < 1.0);
+    assert!(diversity.mean_gene_entropy > 0.9);
+    assert_eq!(64, diversity.num_distinct_phenotypes);
+}
@@ -0,0 +1,77 @@
+//! Fitness caching
+//! Individuals with an already seen genome are not evaluated again by the task
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+
+fn pla_can_learn_bi_cached(operation_type: BinOp, vector_size: usize, max_generation: usize) {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+    let max_fitness = bin_task.max_fitness();
+
+    let mut pop_pla : Population<ProgrammableLogicArray, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 16);
+    pop_pla.enable_fitness_cache();
+    for _ in 0..4 {
+        pop_pla.add_unrated_individual(ProgrammableLogicArray::new_null(vector_size * 2, vector_size));
+    }
+    pop_pla.rate_unrated_individuals();
+    pop_pla.set_num_selected(4);
+    pop_pla.set_num_offspring(8);
+
+    pop_pla.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_pla.best_fitness());
+    let num_rated = 4 + pop_pla.generation() * 8;
+    assert_eq!(num_rated, pop_pla.cache_hits() + pop_pla.cache_misses());
+    assert_eq!(pop_pla.cache_misses(), pop_pla.num_evaluations());
+}
+
+#[test]
+fn pla_can_learn_bi_and1_cached() {
+    for _ in 0..10 {
+        pla_can_learn_bi_cached(BinOp::AND, 1, 1000);
+    }
+}
+
+#[test]
+fn pla_can_learn_bi_xor1_cached() {
+    for _ in 0..10 {
+        pla_can_learn_bi_cached(BinOp::XOR, 1, 1000);
+    }
+}
+
+#[test]
+fn equal_individuals_evaluated_once() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::OR, 4));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 10);
+    pop_tt.enable_fitness_cache();
+    pop_tt.set_num_threads(2);
+    for _ in 0..5 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(4, 2));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.add_and_rate_individual(TruthTable::new_null(4, 2));
+    assert_eq!(6, pop_tt.len());
+    assert_eq!(1, pop_tt.num_evaluations());
+    assert_eq!(1, pop_tt.cache_misses());
+    assert_eq!(5, pop_tt.cache_hits());
+}
+
+#[test]
+fn cache_disabled_by_default() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::OR, 4));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 10);
+    for _ in 0..5 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(4, 2));
+    }
+    pop_tt.rate_unrated_individuals();
+    assert_eq!(5, pop_tt.num_evaluations());
+    assert_eq!(0, pop_tt.cache_hits() + pop_tt.cache_misses());
+}
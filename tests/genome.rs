@@ -0,0 +1,125 @@
+//! Genomes
+//! Every individual exposes its genes uniformly, so generic operators work the same on all of them
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    ProgrammableLogicArray,
+    SerialAdder,
+    TruthTable,
+};
+use tetani::genetic::{
+    Crossover,
+    Individual,
+};
+use tetani::genetic::genome::{
+    genome_crossover,
+    genome_distance,
+    Gene,
+    Genome,
+};
+use tetani::genetic::mutation::SelfAdaptive;
+use tetani::genetic::niching::GenotypeDistance;
+use tetani::genetic::sequence::Recurrent;
+use tetani::neural::NeuralNetwork;
+
+/// The genes round trip, and mutate_gene only changes the gene with the same number.
+fn genes_match_mutate_gene<I: Individual + Genome + Clone + PartialEq + std::fmt::Debug>(indi: &I, rng: &mut StdRng) {
+    let genes = indi.genes();
+    assert!(genes.len() >= indi.num_genes());
+    let mut copy = indi.clone();
+    copy.set_genes(&genes);
+    assert_eq!(*indi, copy);
+
+    for gene in 0..indi.num_genes() {
+        let mut mutated = indi.clone();
+        mutated.mutate_gene(gene, rng);
+        let mutated_genes = mutated.genes();
+        for k in 0..genes.len() {
+            if k == gene {
+                assert_ne!(genes[k], mutated_genes[k]);
+            } else {
+                assert_eq!(genes[k], mutated_genes[k]);
+            }
+        }
+    }
+}
+
+fn genome_distance_matches_genotype_distance<I: Individual + Genome + GenotypeDistance>(indi: &I, other: &I) {
+    assert_eq!(indi.genotype_distance(other), genome_distance(indi, other));
+    assert_eq!(0.0, genome_distance(indi, indi));
+}
+
+#[test]
+fn genes_of_each_individual() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..10 {
+        let tt = TruthTable::new_rand(3, 2, &mut rng);
+        genes_match_mutate_gene(&tt, &mut rng);
+        genome_distance_matches_genotype_distance(&tt, &TruthTable::new_rand(3, 2, &mut rng));
+
+        let pla = ProgrammableLogicArray::new_rand(2, 2, &mut rng);
+        genes_match_mutate_gene(&pla, &mut rng);
+        genome_distance_matches_genotype_distance(&pla, &ProgrammableLogicArray::new_rand(2, 2, &mut rng));
+
+        let nn = NeuralNetwork::new_muta(3, 2, 2, 10, &mut rng);
+        genes_match_mutate_gene(&nn, &mut rng);
+        genome_distance_matches_genotype_distance(&nn, &NeuralNetwork::new_muta(3, 2, 2, 10, &mut rng));
+
+        let recurrent = Recurrent::new(TruthTable::new_rand(3, 2, &mut rng), 1);
+        genes_match_mutate_gene(&recurrent, &mut rng);
+    }
+}
+
+#[test]
+fn kinds_of_genes() {
+    let mut rng = StdRng::seed_from_u64(1);
+    assert!(TruthTable::new_rand(2, 1, &mut rng).genes().iter().all(|gene| matches!(gene, Gene::Bit(_))));
+    let nn = NeuralNetwork::new_muta(2, 1, 1, 5, &mut rng);
+    assert_eq!(nn.num_genes(), nn.genes().len());
+    assert!(nn.genes().iter().all(|gene| matches!(gene, Gene::Integer(_))));
+
+    // The rate follows the genes of the wrapped individual
+    let self_adaptive = SelfAdaptive::new(TruthTable::new_null(2, 1), 0.25);
+    let mut genes = self_adaptive.genes();
+    assert_eq!(5, genes.len());
+    assert_eq!(Gene::Real(0.25), genes[4]);
+    genes[0] = Gene::Bit(true);
+    genes[4] = Gene::Real(0.5);
+    let mut changed = self_adaptive.clone();
+    changed.set_genes(&genes);
+    assert_eq!(0.5, changed.rate());
+    assert_eq!(vec![true], changed.calculate_output(&[false, false]));
+    assert_eq!(0.25 + 1.0, genome_distance(&self_adaptive, &changed));
+
+    // Individuals that don't evolve have no genes
+    assert!(BinaryIndividual::new(BinOp::XOR, 4).genes().is_empty());
+    assert!(SerialAdder::new().genes().is_empty());
+}
+
+#[test]
+fn genome_crossover_takes_each_gene_from_a_parent() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for crossover in [Crossover::OnePoint, Crossover::TwoPoint, Crossover::Uniform].iter() {
+        for _ in 0..100 {
+            let parent_a = NeuralNetwork::new_muta(3, 2, 2, 20, &mut rng);
+            let parent_b = NeuralNetwork::new_muta(3, 2, 2, 20, &mut rng);
+            let child = genome_crossover(&parent_a, &parent_b, crossover, &mut rng);
+            let genes_a = parent_a.genes();
+            let genes_b = parent_b.genes();
+            for (k, gene) in child.genes().iter().enumerate() {
+                assert!(*gene == genes_a[k] || *gene == genes_b[k]);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn genes_must_be_of_the_right_kind() {
+    let mut tt = TruthTable::new_null(1, 1);
+    tt.set_genes(&[Gene::Bit(true), Gene::Integer(1)]);
+}
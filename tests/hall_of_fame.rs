@@ -0,0 +1,151 @@
+//! Hall of fame
+//! The best individuals ever rated are kept even when the population loses them
+
+mod common;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    behaviour,
+    ImitationTask,
+    Population,
+    Replacement,
+    Task,
+};
+use tetani::genetic::checkpoint::{
+    CheckpointReader,
+    CheckpointWriter,
+};
+use tetani::genetic::hall_of_fame::HallOfFame;
+
+fn new_pop_tt(in_size: usize, out_size: usize) -> Population<TruthTable, ImitationTask<TruthTable>> {
+    let mut rng = rand::thread_rng();
+    let indis = (0..4).map(|_| TruthTable::new_rand(in_size, out_size, &mut rng)).collect();
+    let mut pop_tt = common::new_pop_tt(common::imitate_rand_tt(in_size, out_size, &mut rng), indis);
+    // Without elites, the best individuals are lost as soon as they are found
+    pop_tt.set_replacement(Replacement::MuCommaLambda);
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(16);
+    pop_tt
+}
+
+fn hall_of_fame_keeps_the_best_ever() {
+    let mut pop_tt = new_pop_tt(3, 2);
+    pop_tt.enable_hall_of_fame(8);
+    let mut best_ever = pop_tt.best_fitness();
+    for _ in 0..100 {
+        pop_tt.next_generation();
+        best_ever = best_ever.max(pop_tt.best_fitness());
+    }
+
+    let hall_of_fame = pop_tt.hall_of_fame().unwrap();
+    assert_eq!(Some(best_ever), hall_of_fame.best_fitness());
+    assert_eq!(8, hall_of_fame.len());
+    let members = hall_of_fame.members();
+    for k in 1..members.len() {
+        assert!(members[k - 1].fitness >= members[k].fitness);
+        for other in members[..k].iter() {
+            assert_ne!(behaviour(&members[k].indi), behaviour(&other.indi));
+        }
+    }
+    for rated in members.iter() {
+        assert_eq!(pop_tt.task.calculate_fitness(&rated.indi), rated.fitness);
+    }
+}
+
+fn tt_can_learn_tt_with_hall_of_fame_elites(max_generation: usize) {
+    let mut pop_tt = new_pop_tt(4, 2);
+    let max_fitness = pop_tt.task.max_fitness();
+    pop_tt.enable_hall_of_fame(2);
+    pop_tt.set_hall_of_fame_elites(2);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+    assert_eq!(Some(max_fitness), pop_tt.hall_of_fame().unwrap().best_fitness());
+}
+
+#[test]
+fn hall_of_fame_keeps_the_best_ever_under_comma_replacement() {
+    for _ in 0..10 {
+        hall_of_fame_keeps_the_best_ever();
+    }
+}
+
+#[test]
+fn tt_can_learn_tt_in4_out2_with_hall_of_fame_elites() {
+    for _ in 0..10 {
+        tt_can_learn_tt_with_hall_of_fame_elites(2000);
+    }
+}
+
+#[test]
+fn individuals_with_the_same_phenotype_are_kept_once() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let null = TruthTable::new_null(2, 1);
+    let mutated = TruthTable::new_muta(2, 1, 1, &mut rng);
+    let mut hall_of_fame : HallOfFame<TruthTable> = HallOfFame::new(2);
+
+    assert!(hall_of_fame.offer(&null, 1));
+    assert!(!hall_of_fame.offer(&null, 1));
+    assert!(hall_of_fame.offer(&null, 2));
+    assert_eq!(1, hall_of_fame.len());
+    assert!(hall_of_fame.offer(&mutated, 1));
+    assert_eq!(2, hall_of_fame.len());
+    assert_eq!(Some(&null), hall_of_fame.best());
+
+    // Full, so only better individuals get in
+    assert!(!hall_of_fame.offer(&TruthTable::new_muta(2, 1, 2, &mut rng), 1));
+    assert!(hall_of_fame.offer(&mutated, 3));
+    assert_eq!(Some(&mutated), hall_of_fame.best());
+    assert_eq!(vec![3, 2], hall_of_fame.members().iter().map(|rated| rated.fitness).collect::<Vec<i32>>());
+    assert_eq!(1, hall_of_fame.elites(1).len());
+    assert_eq!(2, hall_of_fame.sample(5, &mut rng).len());
+}
+
+#[test]
+fn hall_of_fame_is_checkpointed() {
+    let mut pop_tt = new_pop_tt(3, 1);
+    pop_tt.set_seed(0);
+    pop_tt.enable_hall_of_fame(4);
+    pop_tt.set_hall_of_fame_elites(1);
+    for _ in 0..10 {
+        pop_tt.next_generation();
+    }
+    let mut writer = CheckpointWriter::new();
+    pop_tt.write_checkpoint(&mut writer);
+    let text = writer.into_string();
+
+    let mut resumed : Population<TruthTable, ImitationTask<TruthTable>> =
+        Population::read_checkpoint(&mut CheckpointReader::new(&text)).unwrap();
+    let members = |pop: &Population<TruthTable, ImitationTask<TruthTable>>| -> Vec<(TruthTable, i32)> {
+        pop.hall_of_fame().unwrap().members().iter().map(|rated| (rated.indi.clone(), rated.fitness)).collect()
+    };
+    assert_eq!(members(&pop_tt), members(&resumed));
+    for _ in 0..20 {
+        pop_tt.next_generation();
+        resumed.next_generation();
+        assert_eq!(pop_tt.stats(), resumed.stats());
+    }
+    assert_eq!(members(&pop_tt), members(&resumed));
+}
+
+#[test]
+fn different_genomes_with_the_same_phenotype_are_kept_once() {
+    let mut rng = StdRng::seed_from_u64(0);
+    // Many more genomes than functions of two inputs
+    let pla = ProgrammableLogicArray::new_rand(2, 1, &mut rng);
+    let same_phenotype = (0..).map(|_| ProgrammableLogicArray::new_rand(2, 1, &mut rng))
+        .find(|other| *other != pla && behaviour(other) == behaviour(&pla)).unwrap();
+    let mut hall_of_fame : HallOfFame<ProgrammableLogicArray> = HallOfFame::new(2);
+
+    assert!(hall_of_fame.offer(&pla, 1));
+    assert!(!hall_of_fame.offer(&same_phenotype, 1));
+    assert!(hall_of_fame.offer(&same_phenotype, 2));
+    assert_eq!(1, hall_of_fame.len());
+    assert_eq!(Some(&same_phenotype), hall_of_fame.best());
+}
@@ -0,0 +1,104 @@
+//! Island model
+//! Individual TruthTable can learn to perfectly imitate BinaryIndividual in several populations exchanging migrants
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Replacement,
+    Task,
+};
+use tetani::genetic::island::{
+    Archipelago,
+    Topology,
+};
+use tetani::genetic::selection::TournamentSelection;
+
+fn new_archipelago(operation_type: BinOp, vector_size: usize, num_islands: usize) -> Archipelago<TruthTable, ImitationTask<BinaryIndividual>> {
+    let mut rng = rand::thread_rng();
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+    let mut islands = Vec::with_capacity(num_islands);
+    for _ in 0..num_islands {
+        let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task.clone(), 8);
+        for _ in 0..4 {
+            pop_tt.add_unrated_individual(TruthTable::new_rand(vector_size * 2, vector_size, &mut rng));
+        }
+        pop_tt.rate_unrated_individuals();
+        pop_tt.set_selection(TournamentSelection::new(2, 1.0));
+        pop_tt.set_num_selected(2);
+        islands.push(pop_tt);
+    }
+    Archipelago::new(islands)
+}
+
+fn tt_can_learn_bi_islands(topology: Topology, operation_type: BinOp, vector_size: usize, max_generation: usize) {
+    let mut archipelago = new_archipelago(operation_type, vector_size, 4);
+    archipelago.set_topology(topology);
+    archipelago.set_migration_interval(5);
+    archipelago.set_num_migrants(2);
+    archipelago.learn_task(max_generation);
+
+    let max_fitness = archipelago.islands()[0].task.max_fitness();
+    assert_eq!(max_fitness, archipelago.best_fitness());
+    for island in archipelago.islands() {
+        assert!(island.len() <= 8);
+        assert_eq!(archipelago.generation(), island.generation());
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_ring() {
+    for _ in 0..10 {
+        tt_can_learn_bi_islands(Topology::Ring, BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_fully_connected() {
+    for _ in 0..10 {
+        tt_can_learn_bi_islands(Topology::FullyConnected, BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn fully_connected_migration_spreads_the_best() {
+    let mut archipelago = new_archipelago(BinOp::AND, 3, 5);
+    archipelago.set_topology(Topology::FullyConnected);
+    let best_fitness = archipelago.best_fitness();
+    archipelago.migrate();
+    for island in archipelago.islands() {
+        assert_eq!(best_fitness, island.best_fitness());
+    }
+}
+
+#[test]
+fn migrants_join_islands_like_offspring() {
+    for replacement in [Replacement::SteadyState, Replacement::MuCommaLambda, Replacement::DeterministicCrowding].iter() {
+        let mut archipelago = new_archipelago(BinOp::AND, 3, 5);
+        for i in 0..5 {
+            let island = archipelago.island_mut(i);
+            island.set_replacement(replacement.clone());
+            island.enable_hall_of_fame(4);
+        }
+        archipelago.set_topology(Topology::FullyConnected);
+        // More migrants than the islands hold so far: they send all their individuals
+        archipelago.set_num_migrants(8);
+        let best_fitness = archipelago.best_fitness();
+        archipelago.migrate();
+        for island in archipelago.islands() {
+            assert!(island.len() <= 8);
+            assert_eq!(Some(best_fitness), island.hall_of_fame().unwrap().best_fitness());
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn islands_cant_send_more_migrants_than_they_hold() {
+    let mut archipelago = new_archipelago(BinOp::AND, 3, 2);
+    archipelago.set_num_migrants(9);
+}
@@ -0,0 +1,187 @@
+//! Mutation strength
+//! Individuals can learn with a fixed number of mutated genes, a probability per gene and adaptive mutation rates
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    Individual,
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+use tetani::genetic::mutation::{
+    Mutation,
+    OneFifthRule,
+    SelfAdaptive,
+};
+use tetani::neural::NeuralNetwork;
+
+fn count_different_outputs<I: Individual>(a: &I, b: &I, in_size: usize) -> usize {
+    let mut input = vec![false; in_size];
+    let mut different = 0;
+    for _ in 0..2usize.pow(in_size as u32) {
+        let output_a = a.calculate_output(&input);
+        let output_b = b.calculate_output(&input);
+        different += output_a.iter().zip(output_b.iter()).filter(|(x, y)| x != y).count();
+        // Next input combination
+        for bit in input.iter_mut() {
+            *bit = !*bit;
+            if *bit {
+                break;
+            }
+        }
+    }
+    different
+}
+
+fn tt_can_learn_bi_mutation(mutation: Mutation, operation_type: BinOp, vector_size: usize, max_generation: usize) -> Population<TruthTable, ImitationTask<BinaryIndividual>> {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+    let max_fitness = bin_task.max_fitness();
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 16);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(vector_size * 2, vector_size));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_mutation(mutation);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+    pop_tt
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_flips() {
+    for _ in 0..10 {
+        tt_can_learn_bi_mutation(Mutation::Flips(2), BinOp::XOR, 2, 5000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_per_gene() {
+    for _ in 0..10 {
+        tt_can_learn_bi_mutation(Mutation::PerGene(0.05), BinOp::XOR, 2, 5000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_one_fifth_rule() {
+    for _ in 0..10 {
+        let pop_tt = tt_can_learn_bi_mutation(Mutation::OneFifthRule(OneFifthRule::new(4.0)), BinOp::XOR, 2, 5000);
+        match pop_tt.mutation() {
+            Mutation::OneFifthRule(rule) => {
+                assert!(rule.strength() >= 1.0);
+                assert!(rule.strength() <= pop_tt.best().num_genes() as f64);
+            },
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_self_adaptive() {
+    for _ in 0..10 {
+        let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 4));
+        let max_fitness = bin_task.max_fitness();
+        let mut pop_tt : Population<SelfAdaptive<TruthTable>, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 16);
+        for _ in 0..4 {
+            pop_tt.add_unrated_individual(SelfAdaptive::new(TruthTable::new_null(4, 2), 0.2));
+        }
+        pop_tt.rate_unrated_individuals();
+        pop_tt.set_num_selected(4);
+
+        pop_tt.learn_task(5000);
+        assert_eq!(max_fitness, pop_tt.best_fitness());
+        let rate = pop_tt.best().rate();
+        assert!(rate > 0.0 && rate <= 1.0);
+    }
+}
+
+#[test]
+fn mutate_gene_changes_one_gene() {
+    let mut rng = rand::thread_rng();
+    let tt = TruthTable::new_rand(3, 2, &mut rng);
+    assert_eq!(16, tt.num_genes());
+    for gene in 0..tt.num_genes() {
+        let mut mutated = tt.clone();
+        mutated.mutate_gene(gene, &mut rng);
+        assert_eq!(1, count_different_outputs(&tt, &mutated, 3));
+    }
+
+    let pla = ProgrammableLogicArray::new_rand(2, 2, &mut rng);
+    assert_eq!(16, pla.num_genes());
+    for gene in 0..pla.num_genes() {
+        let mut mutated = pla.clone();
+        mutated.mutate_gene(gene, &mut rng);
+        assert_ne!(pla, mutated);
+        mutated.mutate_gene(gene, &mut rng);
+        assert_eq!(pla, mutated);
+    }
+
+    let nn = NeuralNetwork::new_muta(3, 2, 2, 10, &mut rng);
+    assert_eq!(3 * 4 + 2 * 4, nn.num_genes());
+    for gene in 0..nn.num_genes() {
+        let mut mutated = nn.clone();
+        mutated.mutate_gene(gene, &mut rng);
+        assert_ne!(nn, mutated);
+    }
+}
+
+#[test]
+fn mutation_strength() {
+    let mut rng = rand::thread_rng();
+    let tt = TruthTable::new_null(4, 2);
+    for num_mutations in 0..5 {
+        let mut mutated = tt.clone();
+        Mutation::Flips(num_mutations).mutate(&mut mutated, &mut rng);
+        assert!(count_different_outputs(&tt, &mutated, 4) <= num_mutations);
+    }
+
+    let mut mutated = tt.clone();
+    Mutation::PerGene(1.0).mutate(&mut mutated, &mut rng);
+    assert_eq!(tt.num_genes(), count_different_outputs(&tt, &mutated, 4));
+
+    let mut mutated = tt.clone();
+    Mutation::PerGene(0.0).mutate(&mut mutated, &mut rng);
+    assert_eq!(tt, mutated);
+}
+
+#[test]
+fn one_fifth_rule_adapts() {
+    let mut mutation = Mutation::OneFifthRule(OneFifthRule::new(4.0));
+    mutation.adapt(5, 10, 32);
+    let increased = match mutation {
+        Mutation::OneFifthRule(ref rule) => rule.strength(),
+        _ => unreachable!(),
+    };
+    assert!(increased > 4.0);
+    for _ in 0..100 {
+        mutation.adapt(0, 10, 32);
+    }
+    assert_eq!(Mutation::OneFifthRule(OneFifthRule::new(1.0)), mutation);
+}
+
+#[test]
+#[should_panic]
+fn per_gene_probability_must_be_valid() {
+    let imitate_bi_xor_2 = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(imitate_bi_xor_2, 16);
+    pop_tt.set_mutation(Mutation::PerGene(1.5));
+}
+
+#[test]
+fn repeated_mutations_saturate_weights() {
+    let mut rng = rand::thread_rng();
+    let mut nn = NeuralNetwork::new_muta(2, 1, 1, 0, &mut rng);
+    // Mutating the same weight many times must not overflow it
+    Mutation::Flips(1000).mutate(&mut nn, &mut rng);
+    for _ in 0..1000 {
+        nn.mutate_gene(0, &mut rng);
+    }
+    assert_eq!(1, nn.calculate_output(&[false, true]).len());
+}
@@ -0,0 +1,115 @@
+//! Niching
+//! Individual TruthTable can learn to perfectly imitate BinaryIndividual with fitness sharing, clearing and deterministic crowding
+
+mod common;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    Individual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    RatedIndividual,
+    Replacement,
+    Task,
+};
+use tetani::genetic::niching::{
+    phenotype_distance,
+    Clearing,
+    FitnessSharing,
+    Genotypic,
+    GenotypeDistance,
+    Phenotypic,
+};
+use tetani::genetic::selection::{
+    TournamentSelection,
+    TruncationSelection,
+};
+
+fn new_pop_tt(operation_type: BinOp, vector_size: usize) -> Population<TruthTable, ImitationTask<BinaryIndividual>> {
+    let mut rng = rand::thread_rng();
+    let indis = (0..16).map(|_| TruthTable::new_rand(vector_size * 2, vector_size, &mut rng)).collect();
+    let mut pop_tt = common::new_pop_tt(common::imitate_bi(operation_type, vector_size), indis);
+    pop_tt.set_num_selected(8);
+    pop_tt
+}
+
+fn learns(mut pop_tt: Population<TruthTable, ImitationTask<BinaryIndividual>>, max_generation: usize) {
+    pop_tt.learn_task(max_generation);
+    assert_eq!(pop_tt.task.max_fitness(), pop_tt.best_fitness());
+    assert!(pop_tt.len() <= pop_tt.capacity());
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_fitness_sharing() {
+    for _ in 0..10 {
+        let mut pop_tt = new_pop_tt(BinOp::XOR, 2);
+        pop_tt.set_selection(FitnessSharing::new(4.0, Genotypic));
+        learns(pop_tt, 5000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_clearing() {
+    for _ in 0..10 {
+        let mut pop_tt = new_pop_tt(BinOp::XOR, 2);
+        pop_tt.set_selection(Clearing::new(3.0, 1, Phenotypic, TournamentSelection::new(2, 1.0)));
+        learns(pop_tt, 5000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_deterministic_crowding() {
+    for _ in 0..10 {
+        let mut pop_tt = new_pop_tt(BinOp::XOR, 2);
+        pop_tt.set_selection(TournamentSelection::new(2, 0.8));
+        pop_tt.set_replacement(Replacement::DeterministicCrowding);
+        pop_tt.set_crossover_rate(0.5);
+        pop_tt.learn_task(5000);
+        assert_eq!(pop_tt.task.max_fitness(), pop_tt.best_fitness());
+        assert_eq!(16, pop_tt.len());
+    }
+    for _ in 0..10 {
+        let mut pop_tt = new_pop_tt(BinOp::XOR, 2);
+        pop_tt.set_replacement(Replacement::DeterministicCrowding);
+        pop_tt.set_distance(Genotypic);
+        learns(pop_tt, 5000);
+    }
+}
+
+#[test]
+fn distances() {
+    let mut rng = rand::thread_rng();
+    let tt = TruthTable::new_rand(3, 2, &mut rng);
+    assert_eq!(0.0, tt.genotype_distance(&tt));
+    assert_eq!(0.0, phenotype_distance(&tt, &tt));
+    let mut mutated = tt.clone();
+    mutated.mutate_gene(0, &mut rng);
+    mutated.mutate_gene(5, &mut rng);
+    assert_eq!(2.0, tt.genotype_distance(&mutated));
+    assert_eq!(2.0, phenotype_distance(&tt, &mutated));
+}
+
+fn rated_tts(tts: Vec<TruthTable>) -> Vec< RatedIndividual<TruthTable> > {
+    tts.into_iter().map(|indi| RatedIndividual{indi, fitness: 1}).collect()
+}
+
+#[test]
+fn crowded_niches_share_fitness() {
+    let null = TruthTable::new_null(2, 1);
+    let mut far = null.clone();
+    for gene in 0..4 {
+        far.mutate_gene(gene, &mut rand::thread_rng());
+    }
+    let pop = rated_tts(vec![null.clone(), null.clone(), null.clone(), far]);
+
+    let sharing = FitnessSharing::new(2.0, Genotypic);
+    // Three identical individuals share the fitness of their niche, the far one keeps it all
+    assert_eq!(vec![1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 1.0], sharing.shared_fitnesses(&pop));
+
+    let clearing = Clearing::new(2.0, 2, Genotypic, TruncationSelection);
+    assert_eq!(vec![0, 1, 3], clearing.winners(&pop));
+}
@@ -1,5 +1,5 @@
 
-/// Individual NeuralNetwork can learn to perfectly imitate BinaryIndividual
+//! Individual NeuralNetwork can learn to perfectly imitate BinaryIndividual
 
 use tetani::digital::{
     BinOp,
@@ -17,6 +17,7 @@ use tetani::neural::{
 
 fn nn_can_learn_bi(operation_type: BinOp, net_depth: usize, vector_size: usize, max_generation: usize) {
 
+    let mut rng = rand::thread_rng();
     let max_fitness = vector_size as i32 * 2i32.pow(vector_size as u32 * 2);
 
     let bi = BinaryIndividual::new(operation_type, vector_size * 2);
@@ -29,18 +30,18 @@ fn nn_can_learn_bi(operation_type: BinOp, net_depth: usize, vector_size: usize,
     let mut pop_nn : Population<NeuralNetwork, ImitationTask<BinaryIndividual>> = Population::new(bin_task, max_generation);
 
     pop_nn.add_unrated_individual(indi);
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32));
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3));
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3));
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3));
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3));
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 2));
-    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 4));
-    // pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 8));
-    // pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 16));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32, &mut rng));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3, &mut rng));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3, &mut rng));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3, &mut rng));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, 3, &mut rng));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 2, &mut rng));
+    pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 4, &mut rng));
+    // pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 8, &mut rng));
+    // pop_nn.add_unrated_individual(NeuralNetwork::new_muta(vector_size * 2, vector_size, net_depth, vector_size as u32 * 16, &mut rng));
     pop_nn.rate_unrated_individuals();
 
-    pop_nn.set_num_selection_truncation(4);
+    pop_nn.set_num_selected(4);
     println!("Initial Neural Network population:");
     // pop_nn.print();
     println!("----------------------------------------------------------");
@@ -48,7 +49,7 @@ fn nn_can_learn_bi(operation_type: BinOp, net_depth: usize, vector_size: usize,
 
     pop_nn.learn_task(max_generation);
     // pop_nn.print();
-    println!("Neural Network total generations: {}", pop_nn.len());
+    println!("Neural Network total generations: {}", pop_nn.generation());
     println!("Best Neural Network:");
     pop_nn.best().print();
     assert_eq!(pop_nn.best_fitness(), max_fitness);
@@ -0,0 +1,116 @@
+//! Novelty search
+//! Individual TruthTable can find a perfect imitation of BinaryIndividual rewarded by the novelty of its behaviour
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    behaviour,
+    ImitationTask,
+    Population,
+    Replacement,
+    Task,
+};
+use tetani::genetic::novelty::{
+    NoveltyTask,
+    NOVELTY_FITNESS_SCALE,
+};
+use tetani::genetic::selection::TournamentSelection;
+use tetani::genetic::termination::TerminationReason;
+
+fn tt_finds_bi_novelty(objective_weight: f64, operation_type: BinOp, vector_size: usize, max_generation: usize) {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+    let max_fitness = bin_task.max_fitness();
+    let mut novelty_task = NoveltyTask::new(bin_task);
+    novelty_task.set_objective_weight(objective_weight);
+    novelty_task.set_num_neighbors(5);
+    novelty_task.set_archive_threshold(3.0);
+
+    let mut pop_tt : Population<TruthTable, NoveltyTask<ImitationTask<BinaryIndividual>>> = Population::new(novelty_task, 20);
+    pop_tt.add_unrated_individual(TruthTable::new_null(vector_size * 2, vector_size));
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_replacement(Replacement::Generational { elites: 2 });
+    pop_tt.set_selection(TournamentSelection::new(2, 1.0));
+    pop_tt.set_num_selected(20);
+
+    assert_eq!(TerminationReason::MaxFitness, pop_tt.learn_task(max_generation));
+    assert!(pop_tt.task.solved());
+    assert_eq!(Some(max_fitness), pop_tt.task.best_objective_fitness());
+    assert!(pop_tt.task.archive_len() > 0);
+}
+
+#[test]
+fn tt_finds_bi_xor1_pure_novelty() {
+    for _ in 0..10 {
+        tt_finds_bi_novelty(0.0, BinOp::XOR, 1, 1000);
+    }
+}
+
+#[test]
+fn tt_finds_bi_xor2_blended_novelty() {
+    for _ in 0..10 {
+        tt_finds_bi_novelty(0.5, BinOp::XOR, 2, 5000);
+    }
+}
+
+#[test]
+fn seen_behaviours_are_not_novel() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+    let mut novelty_task = NoveltyTask::new(bin_task.clone());
+    novelty_task.set_num_neighbors(1);
+    let tt_null = TruthTable::new_null(2, 1);
+    assert_eq!(vec![false; 4], behaviour(&tt_null));
+
+    // With an empty archive the novelty is the length of the behaviour, and rating doesn't archive
+    assert_eq!(4 * NOVELTY_FITNESS_SCALE as i32, novelty_task.calculate_fitness(&tt_null));
+    assert_eq!(0, novelty_task.archive_len());
+    assert!(novelty_task.archive_behaviours(&[&tt_null, &tt_null]));
+    assert_eq!(1, novelty_task.archive_len());
+    assert_eq!(0, novelty_task.calculate_fitness(&tt_null));
+    assert!(!novelty_task.archive_behaviours(&[&tt_null]));
+    assert_eq!(Some(3), novelty_task.best_objective_fitness());
+    assert!(!novelty_task.solved());
+
+    // Only the objective counts with weight 1
+    let mut objective_task = NoveltyTask::new(bin_task.clone());
+    objective_task.set_objective_weight(1.0);
+    let fitness = bin_task.calculate_fitness(&tt_null);
+    assert_eq!(fitness * NOVELTY_FITNESS_SCALE as i32, objective_task.calculate_fitness(&tt_null));
+}
+
+fn tt_novelty_seeded(seed: u64, max_generation: usize, num_threads: usize, fitness_cache: bool) -> (TruthTable, i32, usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tt = TruthTable::new_rand(3, 1, &mut rng);
+    let mut novelty_task = NoveltyTask::new(ImitationTask::new(tt));
+    novelty_task.set_objective_weight(0.5);
+    novelty_task.set_num_neighbors(3);
+
+    let mut pop_tt : Population<TruthTable, NoveltyTask<ImitationTask<TruthTable>>> = Population::new(novelty_task, 16);
+    pop_tt.set_seed(seed);
+    pop_tt.set_num_threads(num_threads);
+    if fitness_cache {
+        pop_tt.enable_fitness_cache();
+    }
+    for _ in 0..8 {
+        let indi = TruthTable::new_rand(3, 1, pop_tt.rng());
+        pop_tt.add_unrated_individual(indi);
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(8);
+    pop_tt.learn_task(max_generation);
+    (pop_tt.best().clone(), pop_tt.best_fitness(), pop_tt.task.archive_len())
+}
+
+#[test]
+fn novelty_same_seed_same_result_in_parallel_and_cached() {
+    for seed in 0..10 {
+        let expected = tt_novelty_seeded(seed, 50, 1, false);
+        assert_eq!(expected, tt_novelty_seeded(seed, 50, 3, false));
+        assert_eq!(expected, tt_novelty_seeded(seed, 50, 3, true));
+    }
+}
@@ -0,0 +1,84 @@
+//! Multi-objective optimization with NSGA-II
+//! Individual ProgrammableLogicArray finds the smallest circuits that perfectly imitate BinaryIndividual
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    ProgrammableLogicArray,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Task,
+};
+use tetani::genetic::multiobjective::{
+    dominates,
+    non_dominated_sort,
+    Nsga2Population,
+    ParsimonyTask,
+};
+use tetani::genetic::termination::{
+    Termination,
+    TerminationReason,
+};
+
+fn pla_finds_smallest_bi(operation_type: BinOp, min_terms: i32, max_generation: usize) {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, 2));
+    let max_fitness = bin_task.max_fitness();
+
+    let mut pop_pla : Nsga2Population<ProgrammableLogicArray, ParsimonyTask<ImitationTask<BinaryIndividual>>> = Nsga2Population::new(ParsimonyTask::new(bin_task), 20);
+    pop_pla.add_unrated_individual(ProgrammableLogicArray::new_null(2, 1));
+    pop_pla.rate_unrated_individuals();
+    pop_pla.set_crossover_rate(0.3);
+    assert_eq!(TerminationReason::MaxGenerations, pop_pla.learn_task(max_generation));
+    assert_eq!(max_generation, pop_pla.generation());
+
+    let front = pop_pla.pareto_front();
+    for a in front.iter() {
+        for b in front.iter() {
+            assert!(!dominates(&a.fitnesses, &b.fitnesses));
+        }
+    }
+    assert!(front.iter().any(|rated| rated.fitnesses == vec![max_fitness, -min_terms]));
+}
+
+#[test]
+fn pla_finds_smallest_bi_and1() {
+    for _ in 0..10 {
+        pla_finds_smallest_bi(BinOp::AND, 1, 100);
+    }
+}
+
+#[test]
+fn pla_finds_smallest_bi_xor1() {
+    for _ in 0..10 {
+        pla_finds_smallest_bi(BinOp::XOR, 2, 200);
+    }
+}
+
+#[test]
+fn non_dominated_fronts() {
+    let fitnesses = vec![
+        vec![1, 5],
+        vec![2, 2],
+        vec![5, 1],
+        vec![1, 1],
+        vec![2, 4],
+        vec![0, 0],
+    ];
+    let fronts = non_dominated_sort(&fitnesses);
+    assert_eq!(fronts, vec![vec![0, 2, 4], vec![1], vec![3], vec![5]]);
+}
+
+#[test]
+fn nsga2_stops_at_max_fitness() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+    let max_fitness = bin_task.max_fitness();
+    for _ in 0..10 {
+        let mut pop_pla : Nsga2Population<ProgrammableLogicArray, ParsimonyTask<ImitationTask<BinaryIndividual>>> = Nsga2Population::new(ParsimonyTask::new(bin_task.clone()), 20);
+        pop_pla.add_unrated_individual(ProgrammableLogicArray::new_null(2, 1));
+        pop_pla.rate_unrated_individuals();
+        let reason = pop_pla.learn_task_until(&[Termination::MaxFitness, Termination::MaxGenerations(1000)]);
+        assert_eq!(TerminationReason::MaxFitness, reason);
+        assert_eq!(max_fitness, pop_pla.best_fitness());
+    }
+}
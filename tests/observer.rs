@@ -0,0 +1,98 @@
+//! Observing the evolution of a population
+//! Observers are notified after every generation with the stats of the population
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+use tetani::genetic::observer::{
+    GenerationStats,
+    HistoryRecorder,
+};
+
+fn tt_learns_bi_recorded(operation_type: BinOp, vector_size: usize, max_generation: usize) {
+    let mut rng = rand::thread_rng();
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+    let max_fitness = bin_task.max_fitness();
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 16);
+    for _ in 0..16 {
+        pop_tt.add_unrated_individual(TruthTable::new_rand(vector_size * 2, vector_size, &mut rng));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    let recorder = HistoryRecorder::new();
+    pop_tt.add_observer(recorder.clone());
+
+    pop_tt.learn_task(max_generation);
+    let history = recorder.history();
+    assert_eq!(pop_tt.generation(), history.len());
+    for (i, stats) in history.iter().enumerate() {
+        assert_eq!(i + 1, stats.generation);
+        assert!(stats.worst_fitness as f64 <= stats.median_fitness);
+        assert!(stats.worst_fitness as f64 <= stats.mean_fitness);
+        assert!(stats.median_fitness <= stats.best_fitness as f64);
+        assert!(stats.mean_fitness <= stats.best_fitness as f64);
+        assert!(stats.num_distinct_genomes >= 1 && stats.num_distinct_genomes <= 16);
+        if i > 0 {
+            assert!(stats.best_fitness >= history[i - 1].best_fitness);
+            assert!(stats.num_evaluations > history[i - 1].num_evaluations);
+        }
+    }
+    let last = history.last().unwrap();
+    assert_eq!(max_fitness, last.best_fitness);
+    assert_eq!(pop_tt.stats(), *last);
+    assert_eq!(history.len() + 1, recorder.to_csv().lines().count());
+}
+
+#[test]
+fn tt_learns_bi_xor2_recorded() {
+    for _ in 0..10 {
+        tt_learns_bi_recorded(BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn closure_observer() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 4);
+    pop_tt.add_unrated_individual(TruthTable::new_null(2, 1));
+    pop_tt.rate_unrated_individuals();
+    let generations = Rc::new(RefCell::new(vec![]));
+    let observed = generations.clone();
+    pop_tt.add_observer(move |stats: &GenerationStats| observed.borrow_mut().push(stats.generation));
+    for _ in 0..5 {
+        pop_tt.next_generation();
+    }
+    assert_eq!(vec![1, 2, 3, 4, 5], *generations.borrow());
+}
+
+#[test]
+fn stats_of_identical_individuals() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 4);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(2, 1));
+    }
+    pop_tt.rate_unrated_individuals();
+    let stats = pop_tt.stats();
+    // The null truth table outputs false, which is right for 3 of the 4 inputs
+    assert_eq!(GenerationStats {
+        generation: 0,
+        best_fitness: 3,
+        mean_fitness: 3.0,
+        median_fitness: 3.0,
+        worst_fitness: 3,
+        num_distinct_genomes: 1,
+        num_evaluations: 4,
+    }, stats);
+}
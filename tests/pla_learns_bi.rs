@@ -1,5 +1,5 @@
 
-/// Individual ProgrammableLogicArray can learn to perfectly imitate BinaryIndividual
+//! Individual ProgrammableLogicArray can learn to perfectly imitate BinaryIndividual
 
 use tetani::digital::{
     BinOp,
@@ -36,7 +36,7 @@ fn pla_can_learn_bi(operation_type: BinOp, vector_size: usize, max_generation: u
 
     pop_tt.learn_task(max_generation);
     // pop_tt.print();
-    println!("Programmable Logic Array total generations: {}", pop_tt.len());
+    println!("Programmable Logic Array total generations: {}", pop_tt.generation());
     println!("Best Programmable Logic Array:");
     pop_tt.best().print();
     assert_eq!(pop_tt.best_fitness(), max_fitness);
@@ -1,6 +1,6 @@
 
-/// Reverse engineer a truth tables using genetic algorithms and an emulation of a Programmable Logic Array
-/// Individual ProgrammableLogicArray can learn to perfectly imitate a TruthTable
+//! Reverse engineer a truth tables using genetic algorithms and an emulation of a Programmable Logic Array
+//! Individual ProgrammableLogicArray can learn to perfectly imitate a TruthTable
 
 use tetani::digital::{
     ProgrammableLogicArray,
@@ -15,7 +15,8 @@ use tetani::genetic::{
 
 fn pla_can_learn_tt(in_size: usize, out_size: usize, max_generation: usize) {
 
-    let tt = TruthTable::new_rand(in_size, out_size);
+    let mut rng = rand::thread_rng();
+    let tt = TruthTable::new_rand(in_size, out_size, &mut rng);
 
     println!("----------------------------------------------------------");
     println!("Target Truth Table:");
@@ -38,7 +39,7 @@ fn pla_can_learn_tt(in_size: usize, out_size: usize, max_generation: usize) {
 
     pop_pla.learn_task(max_generation);
     // pop_pla.print();
-    println!("Truth Table total generations: {}", pop_pla.len());
+    println!("Truth Table total generations: {}", pop_pla.generation());
     println!("Best Truth Table:");
     pop_pla.best().print();
     assert_eq!(max_fitness, pop_pla.best_fitness());
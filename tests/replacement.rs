@@ -0,0 +1,89 @@
+//! Bounded populations with different replacement policies
+//! Individual TruthTable can learn to perfectly imitate BinaryIndividual without the population outgrowing its capacity
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Replacement,
+    Task,
+};
+use tetani::genetic::selection::TournamentSelection;
+
+fn tt_can_learn_bi_replacement(replacement: Replacement, num_offspring: usize, operation_type: BinOp, vector_size: usize, max_generation: usize) {
+    let capacity = 16;
+    let mut rng = rand::thread_rng();
+    let bi = BinaryIndividual::new(operation_type, vector_size * 2);
+    let bin_task = ImitationTask::new(bi);
+    let max_fitness = bin_task.max_fitness();
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, capacity);
+    for _ in 0..capacity {
+        pop_tt.add_unrated_individual(TruthTable::new_rand(vector_size * 2, vector_size, &mut rng));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_replacement(replacement);
+    pop_tt.set_selection(TournamentSelection::new(2, 1.0));
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(num_offspring);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+    assert!(pop_tt.len() <= capacity);
+    assert_eq!(capacity + pop_tt.generation() * num_offspring, pop_tt.num_evaluations());
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_steady_state() {
+    for _ in 0..10 {
+        tt_can_learn_bi_replacement(Replacement::SteadyState, 4, BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_generational() {
+    for _ in 0..10 {
+        tt_can_learn_bi_replacement(Replacement::Generational { elites: 1 }, 16, BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_mu_plus_lambda() {
+    for _ in 0..10 {
+        tt_can_learn_bi_replacement(Replacement::MuPlusLambda, 4, BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_mu_comma_lambda() {
+    for _ in 0..10 {
+        tt_can_learn_bi_replacement(Replacement::MuCommaLambda, 32, BinOp::XOR, 2, 2000);
+    }
+}
+
+#[test]
+fn population_never_exceeds_capacity() {
+    let mut rng = rand::thread_rng();
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 4));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 3);
+    for _ in 0..10 {
+        pop_tt.add_unrated_individual(TruthTable::new_rand(4, 2, &mut rng));
+    }
+    pop_tt.rate_unrated_individuals();
+    assert_eq!(3, pop_tt.len());
+    assert_eq!(10, pop_tt.num_evaluations());
+
+    pop_tt.set_num_selected(2);
+    for _ in 0..100 {
+        let best_fitness = pop_tt.best_fitness();
+        pop_tt.next_generation();
+        assert!(pop_tt.best_fitness() >= best_fitness);
+        assert_eq!(3, pop_tt.len());
+    }
+    assert_eq!(100, pop_tt.generation());
+    assert_eq!(210, pop_tt.num_evaluations());
+}
@@ -0,0 +1,84 @@
+//! Reproducible evolution
+//! Runs with the same seed produce identical populations, regardless of the number of threads used to rate individuals
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    Crossover,
+    ImitationTask,
+    Population,
+};
+use tetani::genetic::selection::TournamentSelection;
+use tetani::neural::{
+    NeuralNetwork,
+};
+
+fn tt_learns_tt_seeded(seed: u64, max_generation: usize, num_threads: usize) -> (TruthTable, i32, usize, usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tt = TruthTable::new_rand(3, 2, &mut rng);
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<TruthTable>> = Population::new(ImitationTask::new(tt), 16);
+    pop_tt.set_seed(seed);
+    pop_tt.set_num_threads(num_threads);
+    for _ in 0..8 {
+        let indi = TruthTable::new_rand(3, 2, pop_tt.rng());
+        pop_tt.add_unrated_individual(indi);
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_selection(TournamentSelection::new(3, 0.8));
+    pop_tt.set_num_selected(4);
+    pop_tt.set_crossover_rate(0.5);
+    pop_tt.set_crossover(Crossover::Uniform);
+
+    pop_tt.learn_task(max_generation);
+    (pop_tt.best().clone(), pop_tt.best_fitness(), pop_tt.generation(), pop_tt.num_evaluations())
+}
+
+fn nn_learns_bi_seeded(seed: u64, max_generation: usize) -> (NeuralNetwork, i32, usize, usize) {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::OR, 4));
+    let mut pop_nn : Population<NeuralNetwork, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 16);
+    pop_nn.set_seed(seed);
+    for _ in 0..8 {
+        let indi = NeuralNetwork::new_muta(4, 2, 2, 3, pop_nn.rng());
+        pop_nn.add_unrated_individual(indi);
+    }
+    pop_nn.rate_unrated_individuals();
+    pop_nn.set_num_selected(4);
+    pop_nn.set_crossover_rate(0.5);
+
+    pop_nn.learn_task(max_generation);
+    (pop_nn.best().clone(), pop_nn.best_fitness(), pop_nn.generation(), pop_nn.num_evaluations())
+}
+
+#[test]
+fn tt_same_seed_same_result() {
+    for seed in 0..20 {
+        assert_eq!(tt_learns_tt_seeded(seed, 300, 1), tt_learns_tt_seeded(seed, 300, 1));
+    }
+}
+
+#[test]
+fn tt_same_seed_same_result_in_parallel() {
+    for seed in 0..20 {
+        assert_eq!(tt_learns_tt_seeded(seed, 300, 1), tt_learns_tt_seeded(seed, 300, 3));
+    }
+}
+
+#[test]
+fn nn_same_seed_same_result() {
+    for seed in 0..20 {
+        assert_eq!(nn_learns_bi_seeded(seed, 300), nn_learns_bi_seeded(seed, 300));
+    }
+}
+
+#[test]
+fn tt_different_seed_different_result() {
+    let results : Vec<(TruthTable, i32, usize, usize)> = (0..20).map(|seed| tt_learns_tt_seeded(seed, 300, 1)).collect();
+    assert!(results.iter().any(|result| *result != results[0]));
+}
@@ -0,0 +1,134 @@
+//! Sampled imitation
+//! Individuals with too many inputs to test them all are rated on a random sample of inputs
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Sampling,
+    Task,
+};
+use tetani::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+
+fn tt_can_learn_bi_sampled(operation_type: BinOp, vector_size: usize, sampling: Sampling, verification: bool, max_generation: usize) {
+    let mut bin_task = ImitationTask::new(BinaryIndividual::new(operation_type.clone(), vector_size * 2));
+    bin_task.set_sampling(sampling);
+    bin_task.set_verification(verification);
+    let exhaustive_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 16);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(vector_size * 2, vector_size));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(8);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(pop_tt.task.max_fitness(), pop_tt.best_fitness());
+    // Verification only lets individuals imitating all the inputs reach the max fitness
+    if verification {
+        assert_eq!(exhaustive_task.max_fitness(), exhaustive_task.calculate_fitness(pop_tt.best()));
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_fixed_sample() {
+    // Inputs never sampled don't affect the fitness, so only the sample can be learnt
+    for seed in 0..10 {
+        tt_can_learn_bi_sampled(BinOp::XOR, 2, Sampling::Fixed { num_samples: 8, seed }, false, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_or2_per_generation_sample() {
+    for seed in 0..10 {
+        tt_can_learn_bi_sampled(BinOp::OR, 2, Sampling::PerGeneration { num_samples: 8, seed }, true, 2000);
+    }
+}
+
+#[test]
+fn sampled_fitness_of_large_inputs() {
+    let mut bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 64));
+    bin_task.set_sampling(Sampling::Fixed { num_samples: 100, seed: 1 });
+    assert_eq!(100, bin_task.samples().len());
+    assert_eq!(32 * 100, bin_task.max_fitness());
+    assert_eq!(bin_task.max_fitness(), bin_task.calculate_fitness(&BinaryIndividual::new(BinOp::XOR, 64)));
+    assert!(bin_task.calculate_fitness(&BinaryIndividual::new(BinOp::AND, 64)) < bin_task.max_fitness());
+}
+
+#[test]
+fn fixed_samples_depend_on_the_seed() {
+    let mut bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 16));
+    bin_task.set_sampling(Sampling::Fixed { num_samples: 10, seed: 7 });
+    let mut same_seed_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 16));
+    same_seed_task.set_sampling(Sampling::Fixed { num_samples: 10, seed: 7 });
+    let mut other_seed_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 16));
+    other_seed_task.set_sampling(Sampling::Fixed { num_samples: 10, seed: 8 });
+    assert_eq!(bin_task.samples(), same_seed_task.samples());
+    assert_ne!(bin_task.samples(), other_seed_task.samples());
+}
+
+#[test]
+fn verification_rejects_individuals_perfect_only_on_the_sample() {
+    // AND and OR only differ when exactly one of their inputs is set
+    let or_2 = BinaryIndividual::new(BinOp::OR, 2);
+    let seed = (0..).find(|&seed| {
+        let mut bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+        bin_task.set_sampling(Sampling::Fixed { num_samples: 1, seed });
+        bin_task.calculate_fitness(&or_2) == bin_task.max_fitness()
+    }).unwrap();
+
+    let mut bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 2));
+    bin_task.set_sampling(Sampling::Fixed { num_samples: 1, seed });
+    bin_task.set_verification(true);
+    assert_eq!(bin_task.max_fitness() - 1, bin_task.calculate_fitness(&or_2));
+    assert_eq!(bin_task.max_fitness(), bin_task.calculate_fitness(&BinaryIndividual::new(BinOp::AND, 2)));
+}
+
+#[test]
+fn population_is_rated_again_on_new_samples() {
+    let mut bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 8));
+    bin_task.set_sampling(Sampling::PerGeneration { num_samples: 4, seed: 3 });
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, 8);
+    pop_tt.set_seed(5);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(8, 4));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(4);
+
+    for _ in 0..20 {
+        let samples = pop_tt.task.samples().to_vec();
+        pop_tt.next_generation();
+        assert_ne!(samples, pop_tt.task.samples());
+        for rated in pop_tt.individuals() {
+            assert_eq!(pop_tt.task.calculate_fitness(&rated.indi), rated.fitness);
+        }
+    }
+}
+
+#[test]
+fn checkpoint_keeps_the_samples() {
+    let mut bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::OR, 32));
+    bin_task.set_sampling(Sampling::PerGeneration { num_samples: 16, seed: 11 });
+    bin_task.set_verification(true);
+    let mut writer = CheckpointWriter::new();
+    bin_task.save(&mut writer);
+    let text = writer.into_string();
+
+    let mut reader = CheckpointReader::new(&text);
+    let restored : ImitationTask<BinaryIndividual> = ImitationTask::load(&mut reader).unwrap();
+    assert_eq!(bin_task.sampling(), restored.sampling());
+    assert_eq!(bin_task.samples(), restored.samples());
+    assert_eq!(bin_task.max_fitness(), restored.max_fitness());
+}
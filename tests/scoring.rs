@@ -0,0 +1,104 @@
+//! Imitation scorings other than Hamming similarity
+//! Individual TruthTable can learn to perfectly imitate another TruthTable with any scoring
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Scoring,
+    Task,
+};
+use tetani::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use tetani::genetic::mutation::Mutation;
+
+fn tt_can_learn_tt_scored(scoring: Scoring, mutation: Mutation, in_size: usize, out_size: usize, max_generation: usize) {
+    let mut rng = rand::thread_rng();
+    let tt = TruthTable::new_rand(in_size, out_size, &mut rng);
+    let hamming_task = ImitationTask::new(tt.clone());
+    let tt_imitation_task = ImitationTask::new_scored(tt, scoring);
+    let max_fitness = tt_imitation_task.max_fitness();
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<TruthTable>> = Population::new(tt_imitation_task, 16);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(TruthTable::new_null(in_size, out_size));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_num_offspring(8);
+    pop_tt.set_mutation(mutation);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+    assert_eq!(hamming_task.max_fitness(), hamming_task.calculate_fitness(pop_tt.best()));
+}
+
+#[test]
+fn tt_can_learn_tt_in3_out2_exact_match() {
+    // Fixing one of two wrong bits of an output doesn't improve its score, so several bits must be mutated at once
+    for _ in 0..10 {
+        tt_can_learn_tt_scored(Scoring::ExactMatch, Mutation::PerGene(0.1), 3, 2, 5000);
+    }
+}
+
+#[test]
+fn tt_can_learn_tt_in3_out3_binary_weights() {
+    for _ in 0..10 {
+        tt_can_learn_tt_scored(Scoring::binary_weights(3), Mutation::Single, 3, 3, 2000);
+    }
+}
+
+#[test]
+fn tt_can_learn_tt_in3_out2_numeric_distance() {
+    for _ in 0..10 {
+        tt_can_learn_tt_scored(Scoring::NumericDistance, Mutation::PerGene(0.1), 3, 2, 5000);
+    }
+}
+
+#[test]
+fn scores_of_each_scoring() {
+    // Little endian: 5 and 3
+    let expected = [true, false, true];
+    let output = [true, true, false];
+    assert_eq!(1, Scoring::Hamming.score(&expected, &output));
+    assert_eq!(0, Scoring::ExactMatch.score(&expected, &output));
+    assert_eq!(1, Scoring::ExactMatch.score(&expected, &expected));
+    assert_eq!(1, Scoring::binary_weights(3).score(&expected, &output));
+    assert_eq!(7, Scoring::binary_weights(3).max_score(3));
+    assert_eq!(11, Scoring::Weighted(vec![1, 5, 6]).score(&expected, &[false, false, true]));
+    assert_eq!(7 - 2, Scoring::NumericDistance.score(&expected, &output));
+    assert_eq!(7, Scoring::NumericDistance.score(&expected, &expected));
+}
+
+#[test]
+fn max_fitness_depends_on_scoring() {
+    let bi_or_4 = BinaryIndividual::new(BinOp::OR, 4);
+    assert_eq!(16 * 2, ImitationTask::new(bi_or_4.clone()).max_fitness());
+    assert_eq!(16, ImitationTask::new_scored(bi_or_4.clone(), Scoring::ExactMatch).max_fitness());
+    assert_eq!(16 * 3, ImitationTask::new_scored(bi_or_4.clone(), Scoring::binary_weights(2)).max_fitness());
+    assert_eq!(16 * 3, ImitationTask::new_scored(bi_or_4, Scoring::NumericDistance).max_fitness());
+}
+
+#[test]
+#[should_panic]
+fn weights_must_match_the_output_size() {
+    ImitationTask::new_scored(BinaryIndividual::new(BinOp::OR, 4), Scoring::Weighted(vec![1, 2, 4]));
+}
+
+#[test]
+fn scoring_is_checkpointed() {
+    let bin_task = ImitationTask::new_scored(BinaryIndividual::new(BinOp::XOR, 4), Scoring::Weighted(vec![3, 1]));
+    let mut writer = CheckpointWriter::new();
+    bin_task.save(&mut writer);
+    let text = writer.into_string();
+    let restored : ImitationTask<BinaryIndividual> = ImitationTask::load(&mut CheckpointReader::new(&text)).unwrap();
+    assert_eq!(bin_task.scoring(), restored.scoring());
+    assert_eq!(bin_task.max_fitness(), restored.max_fitness());
+}
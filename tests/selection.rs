@@ -0,0 +1,161 @@
+//! Selection strategies other than truncation
+//! Individual TruthTable can learn to perfectly imitate BinaryIndividual with any selection strategy
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    RatedIndividual,
+    Task,
+};
+use tetani::genetic::selection::{
+    Epsilon,
+    LexicaseSelection,
+    LinearRankSelection,
+    RouletteWheelSelection,
+    Selection,
+    StochasticUniversalSampling,
+    TournamentSelection,
+};
+
+fn tt_can_learn_bi_selection<S>(selection: S, operation_type: BinOp, vector_size: usize, max_generation: usize)
+    where S: Selection<TruthTable> + 'static {
+
+    tt_can_learn_bi_selection_bounded(selection, operation_type, vector_size, max_generation, max_generation);
+}
+
+fn tt_can_learn_bi_selection_bounded<S>(selection: S, operation_type: BinOp, vector_size: usize, max_generation: usize, capacity: usize)
+    where S: Selection<TruthTable> + 'static {
+
+    let mut rng = rand::thread_rng();
+    let bi = BinaryIndividual::new(operation_type, vector_size * 2);
+    let bin_task = ImitationTask::new(bi);
+    let max_fitness = bin_task.max_fitness();
+
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task, capacity);
+    for _ in 0..8 {
+        pop_tt.add_unrated_individual(TruthTable::new_rand(vector_size * 2, vector_size, &mut rng));
+    }
+    pop_tt.rate_unrated_individuals();
+    pop_tt.set_num_selected(4);
+    pop_tt.set_selection(selection);
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_tournament() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection(TournamentSelection::new(16, 1.0), BinOp::XOR, 2, 20000);
+        tt_can_learn_bi_selection(TournamentSelection::new(32, 0.8), BinOp::XOR, 2, 20000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor1_roulette_wheel() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection(RouletteWheelSelection, BinOp::XOR, 1, 20000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor1_stochastic_universal_sampling() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection(StochasticUniversalSampling, BinOp::XOR, 1, 20000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor1_linear_rank() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection(LinearRankSelection::new(2.0), BinOp::XOR, 1, 20000);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_tournament_bounded() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection_bounded(TournamentSelection::new(2, 1.0), BinOp::XOR, 2, 2000, 32);
+        tt_can_learn_bi_selection_bounded(TournamentSelection::new(4, 0.8), BinOp::XOR, 2, 2000, 32);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_roulette_wheel_bounded() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection_bounded(RouletteWheelSelection, BinOp::XOR, 2, 5000, 32);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_stochastic_universal_sampling_bounded() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection_bounded(StochasticUniversalSampling, BinOp::XOR, 2, 5000, 32);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_linear_rank_bounded() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection_bounded(LinearRankSelection::new(2.0), BinOp::XOR, 2, 5000, 32);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_lexicase() {
+    for _ in 0..10 {
+        tt_can_learn_bi_selection_bounded(LexicaseSelection::new(), BinOp::XOR, 2, 2000, 32);
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_epsilon_lexicase() {
+    let mut lexicase = LexicaseSelection::new();
+    lexicase.set_epsilon(Epsilon::MedianAbsoluteDeviation);
+    for _ in 0..10 {
+        tt_can_learn_bi_selection_bounded(lexicase.clone(), BinOp::XOR, 2, 2000, 32);
+    }
+}
+
+#[test]
+fn case_errors_add_up_to_the_fitness() {
+    let mut rng = rand::thread_rng();
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::AND, 4));
+    for _ in 0..10 {
+        let tt = TruthTable::new_rand(4, 2, &mut rng);
+        let case_errors = bin_task.calculate_case_errors(&tt);
+        assert_eq!(16, case_errors.len());
+        assert_eq!(bin_task.max_fitness() - bin_task.calculate_fitness(&tt), case_errors.iter().sum::<i32>());
+    }
+}
+
+#[test]
+fn lexicase_selects_specialists() {
+    let pop : Vec< RatedIndividual<TruthTable> > = (0..4).map(|_| RatedIndividual {
+        indi: TruthTable::new_null(2, 1),
+        fitness: 0,
+    }).collect();
+    // The first individual is the best overall, but only the last one solves the third case
+    let case_errors = vec![
+        vec![0, 0, 1, 0],
+        vec![1, 0, 1, 0],
+        vec![1, 1, 1, 1],
+        vec![1, 1, 0, 1],
+    ];
+    let mut rng = rand::thread_rng();
+    let selected = LexicaseSelection::new().select_by_cases(&pop, &case_errors, 100, &mut rng);
+    assert!(selected.iter().all(|&position| position == 0 || position == 3));
+    assert!(selected.contains(&0));
+    assert!(selected.contains(&3));
+
+    let mut tolerant = LexicaseSelection::new();
+    tolerant.set_epsilon(Epsilon::Fixed(1));
+    let selected = tolerant.select_by_cases(&pop, &case_errors, 100, &mut rng);
+    assert!(selected.contains(&1));
+    assert!(selected.contains(&2));
+}
@@ -0,0 +1,109 @@
+//! Sequential tasks
+//! Recurrent individuals keep a state between steps, so they can learn sequential circuits
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    Individual,
+    SequenceDetector,
+    SerialAdder,
+    TruthTable,
+};
+use tetani::genetic::{
+    Population,
+    Replacement,
+    Task,
+};
+use tetani::genetic::checkpoint::{
+    Checkpoint,
+    CheckpointReader,
+    CheckpointWriter,
+};
+use tetani::genetic::selection::LexicaseSelection;
+use tetani::genetic::sequence::{
+    random_input_sequences,
+    run_sequence,
+    Recurrent,
+    SequenceTask,
+};
+
+fn bits(text: &str) -> Vec<bool> {
+    text.chars().map(|c| c == '1').collect()
+}
+
+fn recurrent_tt_can_learn(reference: &dyn Individual, state_size: usize, num_sequences: usize, length: usize, max_generation: usize) {
+    let in_size = reference.input_size();
+    let out_size = reference.output_size();
+    let sequence_task = SequenceTask::imitate(reference, random_input_sequences(in_size, num_sequences, length, 0));
+    let max_fitness = sequence_task.max_fitness();
+
+    let mut pop_tt : Population<Recurrent<TruthTable>, SequenceTask> = Population::new(sequence_task, 32);
+    for _ in 0..4 {
+        pop_tt.add_unrated_individual(Recurrent::new(TruthTable::new_null(in_size + state_size, out_size + state_size), state_size));
+    }
+    pop_tt.rate_unrated_individuals();
+    // The state only pays off once both writing and reading it have evolved, so the population must be allowed to drift
+    pop_tt.set_num_selected(32);
+    pop_tt.set_replacement(Replacement::Generational { elites: 2 });
+    pop_tt.set_selection(LexicaseSelection::new());
+
+    pop_tt.learn_task(max_generation);
+    assert_eq!(max_fitness, pop_tt.best_fitness());
+
+    // Sequences never seen while learning
+    let test_task = SequenceTask::imitate(reference, random_input_sequences(in_size, num_sequences, length, 1));
+    assert_eq!(test_task.max_fitness(), test_task.calculate_fitness(pop_tt.best()));
+}
+
+#[test]
+fn recurrent_tt_can_learn_serial_adder() {
+    for _ in 0..10 {
+        recurrent_tt_can_learn(&SerialAdder::new(), 1, 16, 16, 2000);
+    }
+}
+
+#[test]
+fn recurrent_tt_can_learn_sequence_detector() {
+    for _ in 0..10 {
+        recurrent_tt_can_learn(&SequenceDetector::new(bits("101")), 2, 16, 16, 5000);
+    }
+}
+
+#[test]
+fn stateless_individuals_cannot_add_serially() {
+    let sequence_task = SequenceTask::imitate(&SerialAdder::new(), random_input_sequences(2, 16, 8, 0));
+    // XOR is the best a stateless individual can do, it's only wrong when there is a carry
+    let xor = BinaryIndividual::new(BinOp::XOR, 2);
+    let fitness = sequence_task.calculate_fitness(&xor);
+    assert!(fitness < sequence_task.max_fitness());
+    assert!(fitness > sequence_task.max_fitness() / 2);
+}
+
+#[test]
+fn serial_adder_adds() {
+    // 6 + 7 = 13, least significant bits first
+    let inputs : Vec< Vec<bool> > = vec![bits("01"), bits("11"), bits("11"), bits("00")];
+    let outputs = run_sequence(&SerialAdder::new(), &inputs);
+    assert_eq!(vec![bits("1"), bits("0"), bits("1"), bits("1")], outputs);
+}
+
+#[test]
+fn sequence_detector_detects_overlapping_patterns() {
+    let inputs : Vec< Vec<bool> > = "1010100".chars().map(|c| vec![c == '1']).collect();
+    let outputs = run_sequence(&SequenceDetector::new(bits("101")), &inputs);
+    let detected : Vec<bool> = outputs.iter().map(|output| output[0]).collect();
+    assert_eq!(bits("0010100"), detected);
+}
+
+#[test]
+fn sequence_task_checkpoint() {
+    let sequence_task = SequenceTask::imitate(&SerialAdder::new(), random_input_sequences(2, 4, 5, 2));
+    let mut writer = CheckpointWriter::new();
+    sequence_task.save(&mut writer);
+    Recurrent::new(TruthTable::new_null(3, 2), 1).save(&mut writer);
+    let text = writer.into_string();
+
+    let mut reader = CheckpointReader::new(&text);
+    assert_eq!(sequence_task, SequenceTask::load(&mut reader).unwrap());
+    assert_eq!(Recurrent::new(TruthTable::new_null(3, 2), 1), Recurrent::load(&mut reader).unwrap());
+}
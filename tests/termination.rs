@@ -0,0 +1,101 @@
+//! Termination criteria
+//! Learning stops as soon as any criterion holds, and reports which one
+
+mod common;
+
+use std::time::Duration;
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+use tetani::genetic::termination::{
+    Termination,
+    TerminationReason,
+};
+
+fn new_pop_tt(operation_type: BinOp, vector_size: usize) -> Population<TruthTable, ImitationTask<BinaryIndividual>> {
+    common::new_pop_tt(common::imitate_bi(operation_type, vector_size), vec![TruthTable::new_null(vector_size * 2, vector_size)])
+}
+
+#[test]
+fn stops_at_max_fitness() {
+    for _ in 0..10 {
+        let mut pop_tt = new_pop_tt(BinOp::XOR, 1);
+        let reason = pop_tt.learn_task_until(&[Termination::MaxFitness, Termination::MaxGenerations(1000)]);
+        assert_eq!(TerminationReason::MaxFitness, reason);
+        assert_eq!(pop_tt.task.max_fitness(), pop_tt.best_fitness());
+    }
+}
+
+#[test]
+fn stops_at_target_fitness() {
+    for _ in 0..10 {
+        let mut pop_tt = new_pop_tt(BinOp::XOR, 3);
+        let target = pop_tt.best_fitness() + 10;
+        let reason = pop_tt.learn_task_until(&[Termination::TargetFitness(target), Termination::MaxGenerations(10000)]);
+        assert_eq!(TerminationReason::TargetFitness, reason);
+        assert!(pop_tt.best_fitness() >= target);
+    }
+}
+
+#[test]
+fn stops_at_max_generations() {
+    let mut pop_tt = new_pop_tt(BinOp::XOR, 3);
+    let reason = pop_tt.learn_task_until(&[Termination::MaxGenerations(20)]);
+    assert_eq!(TerminationReason::MaxGenerations, reason);
+    assert_eq!(20, pop_tt.generation());
+}
+
+#[test]
+fn stops_at_max_evaluations() {
+    let mut pop_tt = new_pop_tt(BinOp::XOR, 3);
+    pop_tt.set_num_offspring(4);
+    let reason = pop_tt.learn_task_until(&[Termination::MaxEvaluations(50)]);
+    assert_eq!(TerminationReason::MaxEvaluations, reason);
+    // 1 initial evaluation and 4 per generation
+    assert_eq!(53, pop_tt.num_evaluations());
+}
+
+#[test]
+fn stops_at_time_budget() {
+    let mut pop_tt = new_pop_tt(BinOp::XOR, 3);
+    let reason = pop_tt.learn_task_until(&[Termination::TimeBudget(Duration::from_millis(50))]);
+    assert_eq!(TerminationReason::TimeBudget, reason);
+}
+
+#[test]
+fn stops_at_stagnation() {
+    let mut pop_tt = new_pop_tt(BinOp::XOR, 1);
+    pop_tt.learn_task(1000);
+    // Nothing can improve once the max fitness is reached
+    let generation = pop_tt.generation();
+    let reason = pop_tt.learn_task_until(&[Termination::Stagnation(30)]);
+    assert_eq!(TerminationReason::Stagnation, reason);
+    assert_eq!(generation + 30, pop_tt.generation());
+}
+
+#[test]
+fn stops_at_predicate() {
+    let mut pop_tt = new_pop_tt(BinOp::XOR, 3);
+    let reason = pop_tt.learn_task_until(&[
+        Termination::MaxGenerations(100),
+        Termination::Predicate(Box::new(|progress| progress.generation == 7)),
+    ]);
+    assert_eq!(TerminationReason::Predicate, reason);
+    assert_eq!(7, pop_tt.generation());
+}
+
+#[test]
+fn first_criterion_wins() {
+    let mut pop_tt = new_pop_tt(BinOp::XOR, 3);
+    let reason = pop_tt.learn_task_until(&[Termination::MaxGenerations(0), Termination::MaxEvaluations(0)]);
+    assert_eq!(TerminationReason::MaxGenerations, reason);
+    assert_eq!(0, pop_tt.generation());
+}
@@ -0,0 +1,132 @@
+//! Single trajectory optimizers
+//! Hill climbing, simulated annealing and tabu search can learn the same targets as a population
+
+use tetani::digital::{
+    BinOp,
+    BinaryIndividual,
+    ProgrammableLogicArray,
+    TruthTable,
+};
+use tetani::genetic::{
+    ImitationTask,
+    Population,
+    Task,
+};
+use tetani::genetic::mutation::Mutation;
+use tetani::genetic::trajectory::{
+    Cooling,
+    HillClimbing,
+    LocalSearch,
+    SimulatedAnnealing,
+    TabuSearch,
+    Trajectory,
+};
+
+fn tt_can_learn_bi_trajectory<S: LocalSearch<TruthTable> + 'static>(search: S, operation_type: BinOp, vector_size: usize, max_generation: usize) -> usize {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(operation_type, vector_size * 2));
+    let max_fitness = bin_task.max_fitness();
+
+    let mut trajectory = Trajectory::new(bin_task, TruthTable::new_null(vector_size * 2, vector_size), search);
+    trajectory.learn_task(max_generation);
+    assert_eq!(max_fitness, trajectory.best_fitness());
+    assert!(trajectory.current_fitness() <= trajectory.best_fitness());
+    trajectory.num_evaluations()
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_hill_climbing() {
+    for _ in 0..10 {
+        tt_can_learn_bi_trajectory(HillClimbing::new(), BinOp::XOR, 2, 5000);
+    }
+}
+
+#[test]
+fn pla_can_learn_bi_xor1_hill_climbing_restarts() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..10 {
+        let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 2));
+        let max_fitness = bin_task.max_fitness();
+        let mut climbing = HillClimbing::new();
+        climbing.set_restarts(20, |rng| ProgrammableLogicArray::new_rand(2, 1, rng));
+
+        let mut trajectory = Trajectory::new(bin_task, ProgrammableLogicArray::new_rand(2, 1, &mut rng), climbing);
+        trajectory.learn_task(5000);
+        assert_eq!(max_fitness, trajectory.best_fitness());
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_simulated_annealing() {
+    let coolings = vec![
+        Cooling::Exponential { initial: 2.0, alpha: 0.99 },
+        Cooling::Linear { initial: 2.0, decrement: 0.01 },
+        Cooling::Logarithmic { initial: 1.0 },
+    ];
+    for cooling in coolings {
+        for _ in 0..10 {
+            tt_can_learn_bi_trajectory(SimulatedAnnealing::new(cooling.clone()), BinOp::XOR, 2, 5000);
+        }
+    }
+}
+
+#[test]
+fn tt_can_learn_bi_xor2_tabu_search() {
+    for _ in 0..10 {
+        tt_can_learn_bi_trajectory(TabuSearch::new(4), BinOp::XOR, 2, 1000);
+    }
+    for _ in 0..10 {
+        let mut tabu = TabuSearch::new(4);
+        tabu.set_num_neighbors(16);
+        tt_can_learn_bi_trajectory(tabu, BinOp::XOR, 2, 5000);
+    }
+}
+
+#[test]
+fn tabu_search_learns_in_one_pass() {
+    // Every wrong bit of the table is a single improving flip away, so each step fixes one of them
+    let num_evaluations = tt_can_learn_bi_trajectory(TabuSearch::new(4), BinOp::OR, 1, 100);
+    // One initial evaluation and 4 neighbours per step, for the 3 wrong bits of the null table
+    assert_eq!(1 + 3 * 4, num_evaluations);
+}
+
+#[test]
+fn cooling_schedules_decrease() {
+    let coolings = vec![
+        Cooling::Exponential { initial: 10.0, alpha: 0.9 },
+        Cooling::Linear { initial: 10.0, decrement: 0.5 },
+        Cooling::Logarithmic { initial: 10.0 },
+    ];
+    for cooling in coolings {
+        for step in 0..100 {
+            assert!(cooling.temperature(step + 1) <= cooling.temperature(step));
+            assert!(cooling.temperature(step) >= 0.0);
+        }
+    }
+}
+
+#[test]
+fn population_and_trajectory_same_target() {
+    let bin_task = ImitationTask::new(BinaryIndividual::new(BinOp::XOR, 4));
+    let mut pop_tt : Population<TruthTable, ImitationTask<BinaryIndividual>> = Population::new(bin_task.clone(), 16);
+    pop_tt.add_unrated_individual(TruthTable::new_null(4, 2));
+    pop_tt.rate_unrated_individuals();
+    pop_tt.learn_task(5000);
+
+    let mut trajectory = Trajectory::new(bin_task, TruthTable::new_null(4, 2), HillClimbing::new());
+    trajectory.learn_task(5000);
+    assert_eq!(pop_tt.best_fitness(), trajectory.best_fitness());
+}
+
+#[test]
+#[should_panic]
+fn hill_climbing_mutation_must_be_valid() {
+    let mut search : HillClimbing<TruthTable> = HillClimbing::new();
+    search.set_mutation(Mutation::PerGene(1.5));
+}
+
+#[test]
+#[should_panic]
+fn simulated_annealing_mutation_must_be_valid() {
+    let mut search : SimulatedAnnealing = SimulatedAnnealing::new(Cooling::Exponential { initial: 2.0, alpha: 0.99 });
+    search.set_mutation(Mutation::PerGene(1.5));
+}
@@ -1,5 +1,5 @@
 
-/// Individual TruthTable can learn to perfectly imitate BinaryIndividual
+//! Individual TruthTable can learn to perfectly imitate BinaryIndividual
 
 use tetani::digital::{
     BinOp,
@@ -36,7 +36,7 @@ fn tt_can_learn_bi(operation_type: BinOp, vector_size: usize, max_generation: us
 
     pop_tt.learn_task(max_generation);
     // pop_tt.print();
-    println!("Truth Table total generations: {}", pop_tt.len());
+    println!("Truth Table total generations: {}", pop_tt.generation());
     println!("Best Truth Table:");
     pop_tt.best().print();
     assert_eq!(pop_tt.best_fitness(), max_fitness);
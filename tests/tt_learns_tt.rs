@@ -1,6 +1,6 @@
 
-/// Reverse engineer a truth tables using genetic algorithms
-/// Individual TruthTable can learn to perfectly imitate another TruthTable
+//! Reverse engineer a truth tables using genetic algorithms
+//! Individual TruthTable can learn to perfectly imitate another TruthTable
 
 use tetani::digital::{
     TruthTable,
@@ -14,7 +14,8 @@ use tetani::genetic::{
 
 fn tt_can_learn_tt(in_size: usize, out_size: usize, max_generation: usize) {
 
-    let tt = TruthTable::new_rand(in_size, out_size);
+    let mut rng = rand::thread_rng();
+    let tt = TruthTable::new_rand(in_size, out_size, &mut rng);
 
     println!("----------------------------------------------------------");
     println!("Target Truth Table:");
@@ -37,7 +38,7 @@ fn tt_can_learn_tt(in_size: usize, out_size: usize, max_generation: usize) {
 
     pop_tt.learn_task(max_generation);
     // pop_tt.print();
-    println!("Truth Table total generations: {}", pop_tt.len());
+    println!("Truth Table total generations: {}", pop_tt.generation());
     println!("Best Truth Table:");
     pop_tt.best().print();
     assert_eq!(max_fitness, pop_tt.best_fitness());